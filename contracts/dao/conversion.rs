@@ -0,0 +1,276 @@
+use crate::errors::Error;
+
+// Decimals of the native token (AZERO/TZERO).
+pub const NATIVE_TOKEN_DECIMALS: u8 = 12;
+
+// Decimals of oracle prices when a pair has no configured decimals (DIA uses 18).
+pub const DEFAULT_PRICE_DECIMALS: u8 = 18;
+
+// Fiat amounts are fixed-point minor units, e.g. cents.
+pub const FIAT_DECIMALS: u8 = 2;
+
+// Quote currency of the oracle pairs used to derive a cross rate
+// when there is no direct crypto/fiat pair, e.g. AZERO/EUR from AZERO/USD and EUR/USD.
+pub const CROSS_RATE_CURRENCY: &str = "USD";
+
+// Convert a fiat amount in minor units to token units with an oracle price.
+// Formula: token_amount = fiat_amount * 10^token_decimals * 10^price_decimals / (price * 10^FIAT_DECIMALS)
+// The result is rounded down. Every step is checked: a zero price returns InvalidPrice,
+// a result that does not fit in u128 returns ConversionOverflow
+// and a result rounded down to 0 returns ZeroSendingAmount.
+pub fn fiat_to_crypto_amount(
+    fiat_amount: u128,
+    price: u128,
+    price_decimals: u8,
+    token_decimals: u8,
+) -> Result<u128, Error> {
+    if price == 0 {
+        return Err(Error::InvalidPrice);
+    }
+
+    let exponent: i32 = token_decimals as i32 + price_decimals as i32 - FIAT_DECIMALS as i32;
+    let amount: u128 = mul_pow10_div(fiat_amount, exponent, price)?;
+    if amount == 0 {
+        return Err(Error::ZeroSendingAmount);
+    }
+    Ok(amount)
+}
+
+// Derive the price of a crypto/fiat pair from two prices in the same quote currency,
+// e.g. AZERO/EUR = AZERO/USD / EUR/USD. The result has DEFAULT_PRICE_DECIMALS decimals.
+// A zero price or a cross price rounded down to 0 returns InvalidPrice.
+pub fn cross_price(
+    crypto_price: u128,
+    crypto_price_decimals: u8,
+    fiat_price: u128,
+    fiat_price_decimals: u8,
+) -> Result<u128, Error> {
+    if crypto_price == 0 || fiat_price == 0 {
+        return Err(Error::InvalidPrice);
+    }
+
+    // cross_price = crypto_price * 10^(DEFAULT_PRICE_DECIMALS + fiat_price_decimals - crypto_price_decimals) / fiat_price
+    let exponent: i32 = DEFAULT_PRICE_DECIMALS as i32 + fiat_price_decimals as i32 - crypto_price_decimals as i32;
+    let price: u128 = mul_pow10_div(crypto_price, exponent, fiat_price)?;
+    if price == 0 {
+        return Err(Error::InvalidPrice);
+    }
+    Ok(price)
+}
+
+// Compute floor(value * 10^exponent / divisor), the exponent can be negative.
+// floor(floor(value / divisor) / 10^n) equals floor(value / (divisor * 10^n)),
+// so a negative exponent divides the rounded down quotient again.
+fn mul_pow10_div(value: u128, exponent: i32, divisor: u128) -> Result<u128, Error> {
+    if exponent >= 0 {
+        return mul_pow10_div_unsigned(value, exponent as u32, divisor);
+    }
+    let quotient: u128 = value / divisor;
+    // 10^39 exceeds u128, so any larger divisor rounds the quotient down to 0.
+    Ok(match 10_u128.checked_pow(exponent.unsigned_abs()) {
+        Some(scale) => quotient / scale,
+        None => 0,
+    })
+}
+
+// Compute floor(value * 10^exponent / divisor) with long division in base 10,
+// so the intermediate product never has to fit in u128.
+fn mul_pow10_div_unsigned(value: u128, exponent: u32, divisor: u128) -> Result<u128, Error> {
+    let mut quotient: u128 = value / divisor;
+    let mut remainder: u128 = value % divisor;
+    for _ in 0..exponent {
+        // remainder * 10 = digit * divisor + next remainder, with digit in 0..=9.
+        let mut digit: u128 = 0;
+        let mut next_remainder: u128 = 0;
+        for _ in 0..10 {
+            if next_remainder >= divisor - remainder {
+                next_remainder -= divisor - remainder;
+                digit += 1;
+            } else {
+                next_remainder += remainder;
+            }
+        }
+        quotient = quotient
+            .checked_mul(10)
+            .and_then(|q| q.checked_add(digit))
+            .ok_or(Error::ConversionOverflow)?;
+        remainder = next_remainder;
+    }
+    Ok(quotient)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Full 256 bit product of two u128 values as (high, low).
+    fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+        let mask: u128 = u64::MAX as u128;
+        let (a_hi, a_lo) = (a >> 64, a & mask);
+        let (b_hi, b_lo) = (b >> 64, b & mask);
+        let lo_lo = a_lo * b_lo;
+        let hi_lo = a_hi * b_lo;
+        let lo_hi = a_lo * b_hi;
+        let hi_hi = a_hi * b_hi;
+        let cross = (lo_lo >> 64) + (hi_lo & mask) + (lo_hi & mask);
+        let low = (cross << 64) | (lo_lo & mask);
+        let high = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (cross >> 64);
+        (high, low)
+    }
+
+    fn add_wide(a: (u128, u128), b: u128) -> (u128, u128) {
+        let (low, carry) = a.1.overflowing_add(b);
+        (a.0 + carry as u128, low)
+    }
+
+    // value * 10^exponent as a 256 bit (high, low) value.
+    fn mul_pow10_wide(value: u128, exponent: u32) -> (u128, u128) {
+        let mut result = (0, value);
+        for _ in 0..exponent {
+            let (high, low) = mul_wide(result.1, 10);
+            result = (result.0 * 10 + high, low);
+        }
+        result
+    }
+
+    // Deterministic xorshift generator, so the property tests are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn next_u128(&mut self) -> u128 {
+            ((self.next() as u128) << 64) | self.next() as u128
+        }
+
+        // A value with a random number of significant bits, to cover tiny and huge prices.
+        fn next_ranged(&mut self) -> u128 {
+            let bits: u32 = (self.next() % 128) as u32 + 1;
+            self.next_u128() >> (128 - bits)
+        }
+    }
+
+    #[test]
+    fn test_fiat_to_crypto_amount() {
+        // 10.00 USD at 0.5 USD per AZERO is 20 AZERO.
+        assert_eq!(
+            fiat_to_crypto_amount(1_000, 500_000_000_000_000_000, 18, 12),
+            Ok(20_000_000_000_000)
+        );
+        // 0.25 USD at 0.5 USD per AZERO is 0.5 AZERO.
+        assert_eq!(
+            fiat_to_crypto_amount(25, 500_000_000_000_000_000, 18, 12),
+            Ok(500_000_000_000)
+        );
+        // Same payout with a 6 decimals stablecoin and an 8 decimals price feed.
+        assert_eq!(fiat_to_crypto_amount(1_000, 50_000_000, 8, 6), Ok(20_000_000));
+        // Results are rounded down, also when the decimals are smaller than the fiat decimals.
+        assert_eq!(fiat_to_crypto_amount(100, 3, 0, 0), Err(Error::ZeroSendingAmount));
+        assert_eq!(fiat_to_crypto_amount(1_000, 3, 0, 0), Ok(3));
+        assert_eq!(fiat_to_crypto_amount(1_000, 3, 1, 0), Ok(33));
+
+        assert_eq!(fiat_to_crypto_amount(1_000, 0, 18, 12), Err(Error::InvalidPrice));
+        assert_eq!(fiat_to_crypto_amount(0, 1, 18, 12), Err(Error::ZeroSendingAmount));
+        assert_eq!(fiat_to_crypto_amount(100, 1, 18, 39), Err(Error::ConversionOverflow));
+        assert_eq!(fiat_to_crypto_amount(u128::MAX, 1, 2, 1), Err(Error::ConversionOverflow));
+        assert_eq!(fiat_to_crypto_amount(u128::MAX, 1, 3, 0), Err(Error::ConversionOverflow));
+        // The intermediate product overflows u128 but the result does not.
+        assert_eq!(
+            fiat_to_crypto_amount(u128::MAX / 10, u128::MAX / 10, 20, 0),
+            Ok(10_u128.pow(18))
+        );
+    }
+
+    #[test]
+    fn test_cross_price() {
+        // AZERO/USD 1.10 and EUR/USD 1.10 make AZERO/EUR 1.00
+        assert_eq!(
+            cross_price(1_100_000_000_000_000_000, 18, 1_100_000_000_000_000_000, 18),
+            Ok(1_000_000_000_000_000_000)
+        );
+        // AZERO/USD 0.55 with 18 decimals and EUR/USD 1.10 with 8 decimals make AZERO/EUR 0.50
+        assert_eq!(
+            cross_price(550_000_000_000_000_000, 18, 110_000_000, 8),
+            Ok(500_000_000_000_000_000)
+        );
+        assert_eq!(cross_price(0, 18, 1, 18), Err(Error::InvalidPrice));
+        assert_eq!(cross_price(1, 18, 0, 18), Err(Error::InvalidPrice));
+        // A cross price below the smallest unit.
+        assert_eq!(cross_price(1, 38, u128::MAX, 0), Err(Error::InvalidPrice));
+        assert_eq!(cross_price(u128::MAX, 0, 1, 18), Err(Error::ConversionOverflow));
+
+        // 11.00 EUR at AZERO/EUR 0.50 is 22 AZERO.
+        let price = cross_price(550_000_000_000_000_000, 18, 110_000_000, 8).unwrap();
+        assert_eq!(
+            fiat_to_crypto_amount(1_100, price, DEFAULT_PRICE_DECIMALS, NATIVE_TOKEN_DECIMALS),
+            Ok(22_000_000_000_000)
+        );
+    }
+
+    // For random amounts, prices and decimals the result is exactly
+    // floor(fiat_amount * 10^token_decimals * 10^price_decimals / (price * 10^FIAT_DECIMALS)),
+    // or an explicit error.
+    #[test]
+    fn test_fiat_to_crypto_amount_is_exact() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..20_000 {
+            let fiat_amount: u128 = rng.next_ranged() >> 64;
+            // Leave room for the 10^FIAT_DECIMALS factor of the reference check.
+            let price: u128 = rng.next_ranged() >> 7;
+            let price_decimals: u8 = (rng.next() % 31) as u8;
+            let token_decimals: u8 = (rng.next() % 31) as u8;
+
+            let result = fiat_to_crypto_amount(fiat_amount, price, price_decimals, token_decimals);
+            if price == 0 {
+                assert_eq!(result, Err(Error::InvalidPrice));
+                continue;
+            }
+            // Both sides are multiplied by 10^FIAT_DECIMALS to keep them integers:
+            // amount * price * 10^FIAT_DECIMALS <= numerator < (amount + 1) * price * 10^FIAT_DECIMALS
+            let numerator = mul_pow10_wide(fiat_amount, token_decimals as u32 + price_decimals as u32);
+            let scaled_price: u128 = price * 10_u128.pow(FIAT_DECIMALS as u32);
+            match result {
+                Ok(amount) => {
+                    let lower = mul_wide(amount, scaled_price);
+                    assert!(lower <= numerator);
+                    assert!(add_wide(lower, scaled_price) > numerator);
+                }
+                Err(Error::ZeroSendingAmount) => {
+                    assert!(numerator < (0, scaled_price));
+                }
+                Err(Error::ConversionOverflow) => {
+                    // The result does not fit in u128: numerator >= (u128::MAX + 1) * scaled_price.
+                    assert!(numerator >= (scaled_price, 0));
+                }
+                Err(error) => panic!("unexpected error {:?}", error),
+            }
+        }
+    }
+
+    // A higher price never pays out more tokens.
+    #[test]
+    fn test_fiat_to_crypto_amount_is_monotonic_in_price() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..5_000 {
+            let fiat_amount: u128 = (rng.next() % 100_000_000) as u128 + 1;
+            let price: u128 = rng.next_ranged() >> 8;
+            let higher_price: u128 = price + (rng.next() as u128) + 1;
+
+            let amount = fiat_to_crypto_amount(fiat_amount, price, 18, 12);
+            let higher_amount = fiat_to_crypto_amount(fiat_amount, higher_price, 18, 12);
+            match (amount, higher_amount) {
+                (Ok(amount), Ok(higher_amount)) => assert!(higher_amount <= amount),
+                (Ok(_), Err(error)) => assert_eq!(error, Error::ZeroSendingAmount),
+                (Err(Error::ZeroSendingAmount), result) => {
+                    assert_eq!(result, Err(Error::ZeroSendingAmount))
+                }
+                (Err(_), _) => {}
+            }
+        }
+    }
+}
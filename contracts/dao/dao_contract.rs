@@ -1,14 +1,37 @@
 #[ink::contract]
 pub mod dao {
+    use ink::codegen::TraitCallBuilder;
     use ink::contract_ref;
+    use ink::prelude::format;
     use ink::prelude::string::String;
+    use ink::prelude::vec;
     use ink::prelude::vec::Vec;
-    use ink::storage::Mapping;
+    use ink::storage::{Lazy, Mapping};
+    use ink::storage::traits::StorageKey;
 
+    use crate::conversion::*;
     use crate::errors::*;
     use crate::types::*;
 
     use dia_oracle_getter::OracleGetters;
+    use psp22::{PSP22Metadata, PSP22};
+
+    // Maximum number of items returned by a paginated getter.
+    pub const MAX_PAGE_LIMIT: u32 = 100;
+
+    // Maximum byte lengths of inline texts, long-form content belongs off-chain behind a content CID.
+    pub const MAX_TITLE_LENGTH: usize = 128;
+    pub const MAX_DESCRIPTION_LENGTH: usize = 1024;
+    pub const MAX_SOCIAL_ACCOUNTS: usize = 10;
+    pub const MAX_SOCIAL_ACCOUNT_LENGTH: usize = 128;
+    // Maximum byte length of a content CID, enough for CIDv1 with a 512-bit digest.
+    pub const MAX_CID_LENGTH: usize = 72;
+
+    // Version of the storage layout of this code.
+    // Bump it and add a migration step to `migrate` when the layout changes.
+    // New storage fields must be Mapping or Lazy fields so that the root storage
+    // of deployed DAOs keeps decoding after an upgrade.
+    pub const STORAGE_VERSION: u32 = 2;
 
     #[ink(storage)]
     pub struct Dao {
@@ -32,21 +55,18 @@ pub mod dao {
         social_accounts: Vec<String>,
         // Workflow steps
         steps: Vec<Step>,
-        // Members of each step.
-        step_members: Vec<Vec<AccountId>>,
         // Funding proposals
-        proposals: Vec<Proposal>,
+        // Key: proposal index, value: Proposal
+        proposals: Mapping<u32, Proposal>,
+        // Number of proposals, also the index of the next proposal.
+        num_proposals: u32,
         // Store proposal voting of each step: 
         // Key: (proposal index, step index), value: ProposalVoting
         proposal_voting_status: Mapping<(u32, u8), ProposalVoting>,
-        // Whitelisted contributors who can fund a DAO.
-        whitelisted_contributors: Vec<AccountId>,
         // Global quorum setting
         global_voting_quorum: u8,
         // Global threshold setting
         global_voting_threshold: u8,
-        // DAO normal members who can make proposals.
-        normal_members: Vec<AccountId>,
         // Only DAO normal members or anyone can create proposals.
         open: bool,
         // Active or Paused
@@ -58,10 +78,457 @@ pub mod dao {
         // Allow step members revoting.
         // If this option is enabled, all proposals must wait until the voting time ends.
         allow_revoting: bool,
-        // Store DAO grant/funding program
-        programs: Vec<Program>,
+        // Programs of storage version 1, moved to `programs` by `migrate`.
+        legacy_programs: Vec<Program>,
         // Store proposal indexes of each program.
-        program_to_proposals: Vec<Vec<u32>>
+        // Key: (program index, i), value: proposal index
+        program_to_proposals: Mapping<(u32, u32), u32>,
+        // Key: program index, value: number of proposals
+        num_program_proposals: Mapping<u32, u32>,
+        // DAO normal members who can make proposals, whitelisted contributors who can fund a DAO
+        // and members of each step, stored as indexed sets.
+        // Key: (member group, member index), value: member
+        members: Mapping<(MemberGroup, u32), AccountId>,
+        // Key: (member group, member), value: member index
+        member_indexes: Mapping<(MemberGroup, AccountId), u32>,
+        // Key: member group, value: number of members
+        num_members: Mapping<MemberGroup, u32>,
+        // Total weight of step members in the FixedWeight voting mode.
+        // Key: step index, value: total weight
+        step_total_weights: Mapping<u8, u128>,
+        // Store the total contribution of each contributor per token.
+        // Key: (contributor, token), value: amount. The zero token address is the native token.
+        contributions: Mapping<(AccountId, AccountId), u128>,
+        // Tokens funded by each contributor.
+        contributor_tokens: Mapping<AccountId, Vec<AccountId>>,
+        // Total funded amount of each token.
+        token_funds: Mapping<AccountId, u128>,
+        // Content revisions of each proposal.
+        // Key: proposal index, value: revisions, oldest first
+        proposal_revisions: Mapping<u32, Vec<ProposalRevision>>,
+        // Store milestones of each proposal.
+        // Key: proposal index, value: milestones
+        proposal_milestones: Mapping<u32, Vec<Milestone>>,
+        // Store milestone voting of each step:
+        // Key: (proposal index, milestone index, step index), value: ProposalVoting
+        milestone_voting_status: Mapping<(u32, u8, u8), ProposalVoting>,
+        // Store vote value of each step member for milestones
+        // Key: (Member address, proposal index, milestone index, step index), value: 1 or 2 or 3.
+        member_milestone_voted: Mapping<(AccountId, u32, u8, u8), u8>,
+        // Voting weight of step members in the FixedWeight voting mode.
+        // Key: (step index, member), value: weight
+        member_weights: Mapping<(u8, AccountId), u128>,
+        // Voting token balances of step members in the TokenWeighted voting mode,
+        // snapshotted when a proposal is created.
+        // Key: (proposal index, step index, member), value: balance
+        voting_weight_snapshots: Mapping<(u32, u8, AccountId), u128>,
+        // Total snapshotted balance of step members.
+        // Key: (proposal index, step index), value: total balance
+        step_total_weight_snapshots: Mapping<(u32, u8), u128>,
+        // Weight of each member vote, used to undo the vote when revoting.
+        // Key: (Member address, proposal index, step index), value: weight
+        member_voted_weight: Mapping<(AccountId, u32, u8), u128>,
+        // Key: (Member address, proposal index, milestone index, step index), value: weight
+        member_milestone_voted_weight: Mapping<(AccountId, u32, u8, u8), u128>,
+        // Vote delegation between members of a step.
+        // Key: (step index, delegator), value: delegate
+        delegations: Mapping<(u8, AccountId), AccountId>,
+        // Key: (step index, delegate), value: delegators
+        delegators: Mapping<(u8, AccountId), Vec<AccountId>>,
+        // Delegate who cast the vote of a member.
+        // Key: (Member address, proposal index, step index), value: delegate
+        member_voted_by: Mapping<(AccountId, u32, u8), AccountId>,
+        // A passed proposal expires if it is not executed within this period after voting ends.
+        // 0 means passed proposals never expire.
+        execution_period: u64,
+        workflow_mode: WorkflowMode,
+        // Time when a step passed in the sequential workflow mode.
+        // Key: (proposal index, step index), value: timestamp
+        step_passed_at: Mapping<(u32, u8), Timestamp>,
+        // Version of the storage layout, updated by `migrate` after an upgrade.
+        storage_version: u32,
+        // Maximum age of an oracle price used by fiat payouts, 0 means no limit.
+        max_price_age: Lazy<u64>,
+        // Maximum deviation of an oracle price from the last settled price of the pair,
+        // in basis points, 0 means no limit.
+        max_price_deviation: Lazy<u32>,
+        // Price of the last fiat payout of each pair.
+        // Key: crypto/fiat pair, value: price
+        settled_prices: Mapping<String, u128>,
+        // Decimals of payout tokens, overriding the native default and PSP22 metadata.
+        token_decimals: Mapping<AccountId, u8>,
+        // Decimals of oracle prices of each crypto/fiat pair, DEFAULT_PRICE_DECIMALS if not set.
+        price_decimals: Mapping<String, u8>,
+        // Fiat payouts use the oracle TWAP over this window in milliseconds
+        // instead of the spot price, 0 means the spot price.
+        twap_window: Lazy<u64>,
+        // Account that can accept the DAO ownership.
+        pending_owner: Lazy<Option<AccountId>>,
+        // Account that can accept the DAO admin role.
+        pending_admin: Lazy<Option<AccountId>>,
+        // Lifecycle state of funding programs, Open if not set.
+        program_states: Mapping<u32, ProgramState>,
+        // Content CIDs of descriptions kept off-chain.
+        content_cids: Mapping<ContentKey, Vec<u8>>,
+        // Budgets of funding programs, programs without a budget are not limited.
+        program_budgets: Mapping<u32, ProgramBudget>,
+        // Treasury amount of each token allocated to program budgets and not paid yet.
+        allocated_budgets: Mapping<AccountId, u128>,
+        // Store DAO grant/funding programs.
+        // Key: program index, value: program
+        programs: Mapping<u32, Program>,
+        num_programs: Lazy<u32>,
+        // Program budget reserved by each proposal and not paid yet.
+        // Key: proposal index, value: amount in the program budget token
+        proposal_commitments: Mapping<u32, u128>,
+        // Members with a recorded vote, cast directly or by a delegate.
+        // Key: (proposal index, step index), value: members
+        proposal_voters: Mapping<(u32, u8), Vec<AccountId>>
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Funded {
+        #[ink(topic)]
+        contributor: AccountId,
+        #[ink(topic)]
+        token: AccountId,
+        amount: u128,
+    }
+
+    #[ink(event)]
+    pub struct DaoInfoUpdated {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ContentCidChanged {
+        #[ink(topic)]
+        key: ContentKey,
+        cid: Option<Vec<u8>>,
+    }
+
+    #[ink(event)]
+    pub struct ProgramCreated {
+        #[ink(topic)]
+        program_index: u32,
+        title: String,
+        start_date: u64,
+        end_date: u64,
+    }
+
+    #[ink(event)]
+    pub struct ProgramUpdated {
+        #[ink(topic)]
+        program_index: u32,
+        title: String,
+    }
+
+    #[ink(event)]
+    pub struct ProgramDatesChanged {
+        #[ink(topic)]
+        program_index: u32,
+        start_date: u64,
+        end_date: u64,
+    }
+
+    #[ink(event)]
+    pub struct ProgramClosed {
+        #[ink(topic)]
+        program_index: u32,
+    }
+
+    #[ink(event)]
+    pub struct ProgramArchived {
+        #[ink(topic)]
+        program_index: u32,
+        released_budget: u128,
+    }
+
+    #[ink(event)]
+    pub struct ProgramBudgetAllocated {
+        #[ink(topic)]
+        program_index: u32,
+        #[ink(topic)]
+        token: AccountId,
+        amount: u128,
+    }
+
+    #[ink(event)]
+    pub struct ProposalCreated {
+        #[ink(topic)]
+        program_index: u32,
+        #[ink(topic)]
+        proposal_index: u32,
+        #[ink(topic)]
+        proposer: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ProposalUpdated {
+        #[ink(topic)]
+        proposal_index: u32,
+        revision: u32,
+        content_hash: Hash,
+        votes_reset: bool,
+    }
+
+    #[ink(event)]
+    pub struct Voted {
+        #[ink(topic)]
+        proposal_index: u32,
+        step_index: u8,
+        #[ink(topic)]
+        voter: AccountId,
+        value: u8,
+        weight: u128,
+    }
+
+    #[ink(event)]
+    pub struct ProposalExecuted {
+        #[ink(topic)]
+        proposal_index: u32,
+        #[ink(topic)]
+        to: AccountId,
+        token: AccountId,
+        amount: u128,
+    }
+
+    #[ink(event)]
+    pub struct ProposalCancelled {
+        #[ink(topic)]
+        proposal_index: u32,
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ProposalRejected {
+        #[ink(topic)]
+        proposal_index: u32,
+    }
+
+    #[ink(event)]
+    pub struct ExecutionPeriodChanged {
+        execution_period: u64,
+    }
+
+    #[ink(event)]
+    pub struct WorkflowModeChanged {
+        workflow_mode: WorkflowMode,
+    }
+
+    #[ink(event)]
+    pub struct StepPassed {
+        #[ink(topic)]
+        proposal_index: u32,
+        #[ink(topic)]
+        step_index: u8,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferStarted {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AdminTransferStarted {
+        #[ink(topic)]
+        previous_admin: AccountId,
+        #[ink(topic)]
+        new_admin: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AdminTransferred {
+        #[ink(topic)]
+        previous_admin: AccountId,
+        #[ink(topic)]
+        new_admin: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Upgraded {
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct Migrated {
+        from_version: u32,
+        to_version: u32,
+    }
+
+    #[ink(event)]
+    pub struct PriceGuardsChanged {
+        max_price_age: u64,
+        max_price_deviation: u32,
+    }
+
+    #[ink(event)]
+    pub struct TokenDecimalsChanged {
+        #[ink(topic)]
+        token: AccountId,
+        decimals: u8,
+    }
+
+    #[ink(event)]
+    pub struct PriceDecimalsChanged {
+        cryto_fiat_key: String,
+        decimals: u8,
+    }
+
+    #[ink(event)]
+    pub struct TwapWindowChanged {
+        twap_window: u64,
+    }
+
+    #[ink(event)]
+    pub struct MilestoneAdded {
+        #[ink(topic)]
+        proposal_index: u32,
+        milestone_index: u8,
+        amount: u128,
+        due_date: u64,
+    }
+
+    #[ink(event)]
+    pub struct MilestoneSubmitted {
+        #[ink(topic)]
+        proposal_index: u32,
+        milestone_index: u8,
+        #[ink(topic)]
+        submitter: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct MilestoneVoted {
+        #[ink(topic)]
+        proposal_index: u32,
+        milestone_index: u8,
+        step_index: u8,
+        #[ink(topic)]
+        voter: AccountId,
+        value: u8,
+        weight: u128,
+    }
+
+    #[ink(event)]
+    pub struct MilestonePaid {
+        #[ink(topic)]
+        proposal_index: u32,
+        milestone_index: u8,
+        #[ink(topic)]
+        to: AccountId,
+        token: AccountId,
+        amount: u128,
+    }
+
+    #[ink(event)]
+    pub struct MemberWeightChanged {
+        #[ink(topic)]
+        step_index: u8,
+        #[ink(topic)]
+        member: AccountId,
+        weight: u128,
+    }
+
+    #[ink(event)]
+    pub struct Delegated {
+        #[ink(topic)]
+        step_index: u8,
+        #[ink(topic)]
+        delegator: AccountId,
+        #[ink(topic)]
+        delegate: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Undelegated {
+        #[ink(topic)]
+        step_index: u8,
+        #[ink(topic)]
+        delegator: AccountId,
+        #[ink(topic)]
+        delegate: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct NormalMemberAdded {
+        #[ink(topic)]
+        member: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct NormalMemberRemoved {
+        #[ink(topic)]
+        member: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ContributorAdded {
+        #[ink(topic)]
+        contributor: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ContributorRemoved {
+        #[ink(topic)]
+        contributor: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct StepMemberAdded {
+        #[ink(topic)]
+        step_index: u8,
+        #[ink(topic)]
+        member: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct StepMemberRemoved {
+        #[ink(topic)]
+        step_index: u8,
+        #[ink(topic)]
+        member: AccountId,
     }
 
     impl Dao {
@@ -69,6 +536,7 @@ pub mod dao {
         // DAO contructor.
         // This function will be called in the DAOFactory contract.
         // Users must interact with DAOFactory to create their own DAO.
+        // Texts and social accounts beyond the inline limits are rejected.
         #[ink(constructor)]
         pub fn new(
             oracle_address:  AccountId,
@@ -87,39 +555,97 @@ pub mod dao {
             normal_members: Vec<AccountId>,
             open: bool,
             allow_revoting: bool,
-        ) -> Self {
-            Self {
+        ) -> Result<Self, Error> {
+            Self::_check_text(&name, &description)?;
+            Self::_check_social_accounts(&social_accounts)?;
+
+            let mut dao = Self {
                 oracle: oracle_address.into(),
                 owner: Self::env().caller(),
-                admin: admin,
-                name: name,
-                description: description,
-                website: website,
-                email: email,
-                address: address,
-                social_accounts: social_accounts,
-                steps: steps,
-                step_members: step_members,
-                proposals: Vec::new(),
+                admin,
+                name,
+                description,
+                website,
+                email,
+                address,
+                social_accounts,
+                steps,
+                proposals: Mapping::default(),
+                num_proposals: 0,
                 proposal_voting_status: Mapping::default(),
-                whitelisted_contributors: whitelisted_contributors,
-                global_voting_quorum: global_voting_quorum,
-                global_voting_threshold: global_voting_threshold,
-                normal_members: normal_members,
-                open: open,
+                global_voting_quorum,
+                global_voting_threshold,
+                open,
                 status: true,
                 member_voted: Mapping::default(),
-                allow_revoting: allow_revoting,
-                programs: Vec::new(),
-                program_to_proposals: Vec::new()
+                allow_revoting,
+                legacy_programs: Vec::new(),
+                program_to_proposals: Mapping::default(),
+                num_program_proposals: Mapping::default(),
+                members: Mapping::default(),
+                member_indexes: Mapping::default(),
+                num_members: Mapping::default(),
+                step_total_weights: Mapping::default(),
+                contributions: Mapping::default(),
+                contributor_tokens: Mapping::default(),
+                token_funds: Mapping::default(),
+                proposal_revisions: Mapping::default(),
+                proposal_milestones: Mapping::default(),
+                milestone_voting_status: Mapping::default(),
+                member_milestone_voted: Mapping::default(),
+                member_weights: Mapping::default(),
+                voting_weight_snapshots: Mapping::default(),
+                step_total_weight_snapshots: Mapping::default(),
+                member_voted_weight: Mapping::default(),
+                member_milestone_voted_weight: Mapping::default(),
+                delegations: Mapping::default(),
+                delegators: Mapping::default(),
+                member_voted_by: Mapping::default(),
+                execution_period: 0,
+                workflow_mode: WorkflowMode::Parallel,
+                step_passed_at: Mapping::default(),
+                storage_version: STORAGE_VERSION,
+                max_price_age: Lazy::default(),
+                max_price_deviation: Lazy::default(),
+                settled_prices: Mapping::default(),
+                token_decimals: Mapping::default(),
+                price_decimals: Mapping::default(),
+                twap_window: Lazy::default(),
+                pending_owner: Lazy::default(),
+                pending_admin: Lazy::default(),
+                program_states: Mapping::default(),
+                content_cids: Mapping::default(),
+                program_budgets: Mapping::default(),
+                allocated_budgets: Mapping::default(),
+                programs: Mapping::default(),
+                num_programs: Lazy::default(),
+                proposal_commitments: Mapping::default(),
+                proposal_voters: Mapping::default()
+            };
+
+            for member in normal_members {
+                dao._add_member(MemberGroup::NormalMember, member);
             }
+            for contributor in whitelisted_contributors {
+                dao._add_member(MemberGroup::WhitelistedContributor, contributor);
+            }
+            for (step_index, members) in step_members.into_iter().enumerate() {
+                for member in members {
+                    dao._add_step_member(step_index as u8, member);
+                }
+            }
+            Ok(dao)
         }
 
         // Only whitelisted contributors can fund a DAO.
         #[ink(message, payable)]
-        pub fn fund(&self) -> Result<(), Error> {
+        pub fn fund(&mut self) -> Result<(), Error> {
+            if !self.status {
+                return Err(Error::DaoPaused);
+            }
+
             let caller = Self::env().caller();
-            if !self.whitelisted_contributors.contains(&caller) {
+            if !self._is_member(MemberGroup::WhitelistedContributor, caller) {
                 return Err(Error::NotWhitelistedContributor);
             }
 
@@ -129,10 +655,49 @@ pub mod dao {
                 return Err(Error::ZeroFundAmount);
             }
 
+            self._add_contribution(caller, AccountId::from([0x00; 32]), amount)?;
+
             Ok(())
 
         }
 
+        // Only whitelisted contributors can fund a DAO with PSP22 tokens.
+        // The contributor must approve this DAO to spend the amount first.
+        #[ink(message)]
+        pub fn fund_token(&mut self, token: AccountId, amount: u128) -> Result<(), Error> {
+            if !self.status {
+                return Err(Error::DaoPaused);
+            }
+
+            let caller = Self::env().caller();
+            if !self._is_member(MemberGroup::WhitelistedContributor, caller) {
+                return Err(Error::NotWhitelistedContributor);
+            }
+
+            if amount == 0 {
+                return Err(Error::ZeroFundAmount);
+            }
+
+            if token == AccountId::from([0x00; 32]) {
+                return Err(Error::InvalidToken);
+            }
+
+            // Pull tokens from the contributor to this DAO.
+            let mut token_ref: contract_ref!(PSP22) = token.into();
+            match token_ref
+                .call_mut()
+                .transfer_from(caller, self.env().account_id(), amount, Vec::new())
+                .try_invoke()
+            {
+                Ok(Ok(Ok(()))) => (),
+                _ => return Err(Error::TokenTransferFailed),
+            }
+
+            self._add_contribution(caller, token, amount)?;
+
+            Ok(())
+        }
+
 
         // owner
         // admin
@@ -189,44 +754,251 @@ pub mod dao {
                 self.address.clone(),
                 self.social_accounts.clone(),
                 self.steps.clone(),
-                self.proposals.len() as u32,
-                self.whitelisted_contributors.len() as u32,
+                self.num_proposals,
+                self._num_members(MemberGroup::WhitelistedContributor),
                 self.global_voting_quorum,
                 self.global_voting_threshold,
-                self.normal_members.len() as u32,
-                self.programs.len() as u32,
+                self._num_members(MemberGroup::NormalMember),
+                self._num_programs(),
                 self.open,
                 self.status,
                 self.allow_revoting
             )
         }
 
+        // Only the DAO admin can update the DAO description and social accounts.
+        #[ink(message)]
+        pub fn update_dao_info(&mut self, description: String, social_accounts: Vec<String>) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            if caller != self.admin {
+                return Err(Error::NotAdmin);
+            }
+
+            Self::_check_text(&self.name, &description)?;
+            Self::_check_social_accounts(&social_accounts)?;
+
+            self.description = description;
+            self.social_accounts = social_accounts;
+
+            self.env().emit_event(DaoInfoUpdated { account: caller });
+            Ok(())
+        }
+
+        // Set or clear (None) the CID of off-chain content, e.g. a markdown description on IPFS.
+        // DAO content is set by the admin, program content by a ProgramManager,
+        // and proposal content by the proposer before voting starts.
+        #[ink(message)]
+        pub fn set_content_cid(&mut self, key: ContentKey, cid: Option<Vec<u8>>) -> Result<(), Error> {
+            if let Some(cid) = &cid {
+                if cid.is_empty() || cid.len() > MAX_CID_LENGTH {
+                    return Err(Error::InvalidContentCid);
+                }
+            }
+
+            match key {
+                ContentKey::DaoDescription | ContentKey::SocialAccounts => {
+                    if Self::env().caller() != self.admin {
+                        return Err(Error::NotAdmin);
+                    }
+                }
+                ContentKey::Program(program_index) => {
+                    self._check_role(Role::ProgramManager)?;
+                    self._check_program_editable(program_index)?;
+                }
+                ContentKey::Proposal(proposal_index) => {
+                    let proposal: Proposal = self._get_proposal(proposal_index)?;
+                    if Self::env().caller() != proposal.proposer {
+                        return Err(Error::NotProposer);
+                    }
+                    if proposal.start_date <= Self::env().block_timestamp() {
+                        return Err(Error::VotingHasStarted);
+                    }
+                }
+            }
+
+            match &cid {
+                Some(cid) => {
+                    self.content_cids.insert(key, cid);
+                }
+                None => self.content_cids.remove(key),
+            }
+
+            // The CID is part of the proposal content revision.
+            if let ContentKey::Proposal(proposal_index) = key {
+                let proposal: Proposal = self._get_proposal(proposal_index)?;
+                self._add_proposal_revision(&proposal);
+            }
+
+            self.env().emit_event(ContentCidChanged { key, cid });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_content_cid(&self, key: ContentKey) -> Option<Vec<u8>> {
+            self.content_cids.get(key)
+        }
 
         // A DAO can have many funding programs at the same time.
         // A funding program can have many proposals.
+        // Only the DAO admin or a ProgramManager can create programs.
+        // An optional (token, amount) budget is allocated from the unallocated treasury balance,
+        // proposals of the program can not request more than its remaining budget.
         #[ink(message)] 
-        pub fn create_program(&mut self, title: String, description: String, start_date: u64, end_date: u64) -> Result<(), Error> {
-            let caller = Self::env().caller();
-            if caller != self.admin {
-                return Err(Error::NotAdmin);
+        pub fn create_program(
+            &mut self,
+            title: String,
+            description: String,
+            start_date: u64,
+            end_date: u64,
+            budget: Option<(AccountId, u128)>,
+        ) -> Result<(), Error> {
+            self._check_role(Role::ProgramManager)?;
+            Self::_check_text(&title, &description)?;
+
+            if end_date < start_date {
+                return Err(Error::InvalidProgramDates);
             }
 
-            let num_programs: u32 = self.programs.len() as u32;
+            let num_programs: u32 = self._num_programs();
+
+            if let Some((token, amount)) = budget {
+                if amount == 0 {
+                    return Err(Error::InvalidProgramBudget);
+                }
+
+                let allocated: u128 = self.allocated_budgets.get(token).unwrap_or_default();
+                if self._get_balance(token)?.saturating_sub(allocated) < amount {
+                    return Err(Error::NotEnoughBalance);
+                }
+
+                let allocated: u128 = allocated.checked_add(amount).ok_or(Error::ArithmeticOverflow)?;
+                self.allocated_budgets.insert(token, &allocated);
+                self.program_budgets.insert(num_programs, &ProgramBudget {
+                    token,
+                    amount,
+                    committed: 0,
+                    paid: 0,
+                });
+
+                self.env().emit_event(ProgramBudgetAllocated {
+                    program_index: num_programs,
+                    token,
+                    amount,
+                });
+            }
 
             let program = Program {
                 program_index: num_programs,
-                title: title,
-                description: description,
-                start_date: start_date,
-                end_date: end_date
+                title: title.clone(),
+                description,
+                start_date,
+                end_date
             };
 
-            self.programs.push(program);
-            self.program_to_proposals.push(Vec::new());
+            self.programs.insert(num_programs, &program);
+            self.num_programs.set(&(num_programs + 1));
+
+            self.env().emit_event(ProgramCreated {
+                program_index: num_programs,
+                title,
+                start_date,
+                end_date,
+            });
+            Ok(())
+        }
+
+        // Only the DAO admin or a ProgramManager can fix the title and description of a program.
+        #[ink(message)]
+        pub fn update_program(&mut self, program_index: u32, title: String, description: String) -> Result<(), Error> {
+            self._check_role(Role::ProgramManager)?;
+            self._check_program_editable(program_index)?;
+            Self::_check_text(&title, &description)?;
+
+            let mut program: Program = self._get_program(program_index)?;
+            program.title = title.clone();
+            program.description = description;
+            self.programs.insert(program_index, &program);
+
+            self.env().emit_event(ProgramUpdated {
+                program_index,
+                title,
+            });
+            Ok(())
+        }
+
+        // Only the DAO admin or a ProgramManager can change the dates of a program, e.g. to extend it.
+        #[ink(message)]
+        pub fn set_program_dates(&mut self, program_index: u32, start_date: u64, end_date: u64) -> Result<(), Error> {
+            self._check_role(Role::ProgramManager)?;
+            self._check_program_editable(program_index)?;
+
+            if end_date < start_date {
+                return Err(Error::InvalidProgramDates);
+            }
+
+            let mut program: Program = self._get_program(program_index)?;
+            program.start_date = start_date;
+            program.end_date = end_date;
+            self.programs.insert(program_index, &program);
+
+            self.env().emit_event(ProgramDatesChanged {
+                program_index,
+                start_date,
+                end_date,
+            });
+            Ok(())
+        }
+
+        // Only the DAO admin or a ProgramManager can close a program early.
+        // A closed program accepts no new proposals, its existing proposals can still be voted and executed.
+        #[ink(message)]
+        pub fn close_program(&mut self, program_index: u32) -> Result<(), Error> {
+            self._check_role(Role::ProgramManager)?;
+            self._check_program_editable(program_index)?;
+
+            if self.get_program_state(program_index) == ProgramState::Closed {
+                return Err(Error::ProgramClosed);
+            }
+
+            self.program_states.insert(program_index, &ProgramState::Closed);
+            self.env().emit_event(ProgramClosed {
+                program_index,
+            });
+            Ok(())
+        }
+
+        // Only the DAO admin or a ProgramManager can archive a closed program.
+        // An archived program is read-only and its uncommitted budget returns to the treasury.
+        #[ink(message)]
+        pub fn archive_program(&mut self, program_index: u32) -> Result<(), Error> {
+            self._check_role(Role::ProgramManager)?;
+            self._check_program_editable(program_index)?;
+
+            if self.get_program_state(program_index) != ProgramState::Closed {
+                return Err(Error::ProgramNotClosed);
+            }
+
+            let mut released_budget: u128 = 0;
+            if let Some(mut budget) = self.program_budgets.get(program_index) {
+                released_budget = budget.amount.saturating_sub(budget.committed);
+                budget.amount = budget.committed;
+                self.program_budgets.insert(program_index, &budget);
+
+                let allocated: u128 = self.allocated_budgets.get(budget.token).unwrap_or_default();
+                self.allocated_budgets.insert(budget.token, &allocated.saturating_sub(released_budget));
+            }
+
+            self.program_states.insert(program_index, &ProgramState::Archived);
+            self.env().emit_event(ProgramArchived {
+                program_index,
+                released_budget,
+            });
             Ok(())
         }
 
         // Create a proposal within a funding program
+        // payment_amount_fiat is in minor units (cents) of the fiat currency of cryto_fiat_key.
+        // A fiat proposal needs an oracle price of its pair, either direct or as a cross rate.
         #[ink(message)]
         pub fn create_proposal(
             &mut self,
@@ -236,27 +1008,31 @@ pub mod dao {
             start_date: u64,
             end_date: u64,
             use_fiat: bool,
-            payment_amount_fiat: u32,
+            payment_amount_fiat: u128,
             cryto_fiat_key: String,
             payment_amount_crypto: u128,
             token: AccountId,
             to: AccountId,
             allow_early_executed: bool,
         ) -> Result<(), Error> {
+            if !self.status {
+                return Err(Error::DaoPaused);
+            }
+
             // Check caller privileges
             let caller = Self::env().caller();
-            if !self.open {
-                if !self.normal_members.contains(&caller) {
-                    return Err(Error::NotANormalMember);
-                }
+            if !self.open && !self._is_member(MemberGroup::NormalMember, caller) {
+                return Err(Error::NotANormalMember);
             }
 
+            Self::_check_text(&title, &description)?;
+
             // Selected program index is existed or not
-            if (self.programs.len() as u32) < program_index {
-                return Err(Error::ProgramIndexOutOfBound);
-            }   
+            let program: Program = self._get_program(program_index)?;
 
-            let program: &Program = &self.programs[program_index as usize];
+            if self.get_program_state(program_index) != ProgramState::Open {
+                return Err(Error::ProgramClosed);
+            }
 
             // Check time conditions
             if program.start_date > Self::env().block_timestamp() {
@@ -267,28 +1043,53 @@ pub mod dao {
                 return Err(Error::ProgramHasEnded);
             }
 
+            // The voting window must be within the program window.
+            if end_date < start_date {
+                return Err(Error::InvalidProposalDates);
+            }
+
+            if start_date < program.start_date || end_date > program.end_date {
+                return Err(Error::ProposalOutsideProgram);
+            }
+
+            Self::_check_payment(use_fiat, payment_amount_fiat, payment_amount_crypto, to)?;
+
+            if use_fiat {
+                self._get_pair_prices(&cryto_fiat_key)?;
+            }
+
+            if let Some(budget) = self.program_budgets.get(program_index) {
+                if budget.token != token {
+                    return Err(Error::ProgramTokenMismatch);
+                }
+            }
+
             // Setup proposal
-            let count_proposal = self.proposals.len() as u32;
+            let count_proposal = self.num_proposals;
 
             let proposal = Proposal {
-                program_index: program_index,
+                program_index,
                 proposal_index: count_proposal,
                 proposer: caller,
-                title: title,
-                description: description,
-                start_date: start_date,
-                end_date: end_date,
-                use_fiat: use_fiat,
-                payment_amount_fiat: payment_amount_fiat,
-                cryto_fiat_key: cryto_fiat_key,
-                payment_amount_crypto: payment_amount_crypto,
-                token: token,
-                to: to,
-                allow_early_executed: allow_early_executed,
+                title,
+                description,
+                start_date,
+                end_date,
+                use_fiat,
+                payment_amount_fiat,
+                cryto_fiat_key,
+                payment_amount_crypto,
+                token,
+                to,
+                allow_early_executed,
                 executed: false,
+                status: ProposalStatus::Pending,
             };
 
-            self.proposals.push(proposal);
+            self._commit_proposal_budget(&proposal)?;
+            self.proposals.insert(count_proposal, &proposal);
+            self.num_proposals += 1;
+            self._add_proposal_revision(&proposal);
 
 
             // Initial proposal voting values for each workflow step.
@@ -307,27 +1108,116 @@ pub mod dao {
                         neutral: 0,
                     },
                 );
+
+                // Snapshot voting token balances of step members.
+                let step: &Step = &self.steps[i as usize];
+                if step.voting_mode == VotingMode::TokenWeighted {
+                    let voting_token: AccountId = step.voting_token;
+                    let mut total_weight: u128 = 0;
+                    for member in self._get_members(MemberGroup::StepMember(i), 0, self._num_members(MemberGroup::StepMember(i))) {
+                        let weight: u128 = self._get_token_balance(voting_token, member)?;
+                        self.voting_weight_snapshots.insert((count_proposal, i, member), &weight);
+                        total_weight = total_weight.checked_add(weight).ok_or(Error::ArithmeticOverflow)?;
+                    }
+                    self.step_total_weight_snapshots.insert((count_proposal, i), &total_weight);
+                }
                 i += 1;
             }
 
             // Update program_to_proposals
-            self.program_to_proposals[program_index  as usize].push(count_proposal);
+            let num_program_proposals: u32 = self.num_program_proposals.get(program_index).unwrap_or_default();
+            self.program_to_proposals.insert((program_index, num_program_proposals), &count_proposal);
+            self.num_program_proposals.insert(program_index, &(num_program_proposals + 1));
+
+            self.env().emit_event(ProposalCreated {
+                program_index,
+                proposal_index: count_proposal,
+                proposer: caller,
+            });
+
+            Ok(())
+        }
+
+        // The proposer can fix the content, payment and beneficiary of a proposal before voting starts.
+        // Every update adds a revision, votes cast on a previous revision are reset.
+        #[ink(message)]
+        pub fn update_proposal(
+            &mut self,
+            proposal_index: u32,
+            title: String,
+            description: String,
+            payment_amount_fiat: u128,
+            payment_amount_crypto: u128,
+            to: AccountId,
+        ) -> Result<(), Error> {
+            if !self.status {
+                return Err(Error::DaoPaused);
+            }
+
+            let mut proposal: Proposal = self._get_proposal(proposal_index)?;
+
+            if Self::env().caller() != proposal.proposer {
+                return Err(Error::NotProposer);
+            }
 
+            if proposal.status == ProposalStatus::Cancelled {
+                return Err(Error::ProposalHasCancelled);
+            }
+
+            if proposal.start_date <= Self::env().block_timestamp() {
+                return Err(Error::VotingHasStarted);
+            }
+
+            Self::_check_text(&title, &description)?;
+            Self::_check_payment(proposal.use_fiat, payment_amount_fiat, payment_amount_crypto, to)?;
+
+            // Milestones must still fit in the payment amount.
+            let mut payment_amount: u128 = payment_amount_crypto;
+            if proposal.use_fiat {
+                payment_amount = payment_amount_fiat;
+            }
+            let milestone_amount: u128 = self.proposal_milestones.get(proposal_index).unwrap_or_default().iter().map(|m| m.amount).sum();
+            if milestone_amount > payment_amount {
+                return Err(Error::MilestoneAmountExceeded);
+            }
+
+            // The budget reservation follows the updated payment amount.
+            self._release_program_budget(proposal.program_index, proposal_index);
+
+            proposal.title = title;
+            proposal.description = description;
+            proposal.payment_amount_fiat = payment_amount_fiat;
+            proposal.payment_amount_crypto = payment_amount_crypto;
+            proposal.to = to;
+            self._commit_proposal_budget(&proposal)?;
+            self.proposals.insert(proposal_index, &proposal);
+
+            let votes_reset: bool = self._reset_votes(proposal_index);
+            let revision: ProposalRevision = self._add_proposal_revision(&proposal);
+
+            self.env().emit_event(ProposalUpdated {
+                proposal_index,
+                revision: revision.revision,
+                content_hash: revision.content_hash,
+                votes_reset,
+            });
             Ok(())
         }
+
         // Value: 1 - agree, 2 - disagree, 3 - abstain
         #[ink(message)]
         pub fn voting(&mut self, proposal_index: u32, step: u8, value: u8) -> Result<(), Error> {
+            if !self.status {
+                return Err(Error::DaoPaused);
+            }
+
             // Check the voting value
             if !Vec::from([1,2,3]).contains(&value) {
                 return Err(Error::IncorrectVotingOption);
             }
 
             // Check the selected proposal index
-            let num_proposals: u32 = self.proposals.len() as u32;
-            if proposal_index >= num_proposals {
-                return Err(Error::ProposalIndexOutOfBound);
-            }
+            let proposal: Proposal = self._get_proposal(proposal_index)?;
 
             // Check voting previleges
             // Caller must be a step member
@@ -335,14 +1225,15 @@ pub mod dao {
                 return Err(Error::NotAllowVoting);
             }
 
-           
-            let proposal: &Proposal = &self.proposals[proposal_index as usize];
-
             // An executed proposal can not be voted.
             if proposal.executed {
                 return Err(Error::ProposalHasExecuted);
             }
 
+            if proposal.status == ProposalStatus::Cancelled {
+                return Err(Error::ProposalHasCancelled);
+            }
+
             // Check time constraint
             if proposal.start_date > Self::env().block_timestamp() {
                 return Err(Error::VotingHasNotStarted);
@@ -352,472 +1243,4497 @@ pub mod dao {
                 return Err(Error::VotingHasEnded);
             }
 
-            // Caller voted or not yet.
+            // In the sequential workflow mode, a step votes only once the previous step has passed.
+            if self.workflow_mode == WorkflowMode::Sequential && !self._is_step_active(&proposal, step) {
+                return Err(Error::StepNotActive);
+            }
+
+            // The previous step passed without a vote, its voting window starts now.
+            if self.workflow_mode == WorkflowMode::Sequential
+                && step > 0
+                && !self.step_passed_at.contains((proposal_index, step - 1))
+            {
+                self.step_passed_at.insert((proposal_index, step - 1), &Self::env().block_timestamp());
+                self.env().emit_event(StepPassed {
+                    proposal_index,
+                    step_index: step - 1,
+                });
+            }
+
+            let caller = Self::env().caller();
+
+            // A member who delegated the vote can not vote directly.
+            if self.delegations.contains((step, caller)) {
+                return Err(Error::VoteDelegated);
+            }
+
             let mut voting_status: ProposalVoting = self
                 .proposal_voting_status
                 .get((proposal_index, step))
                 .unwrap_or_default();
 
-            let voted_value: u8 = self
-                .member_voted
-                .get((Self::env().caller(), proposal_index, step))
-                .unwrap_or_default();
-
-            // If the caller voted and this DAO allows users revoting.
-            if voted_value != 0 {
-                if self.allow_revoting {
-                    if voted_value == value {
-                        return Err(Error::SameVotingOption);
-                    }
-                    if voted_value == 1 {
-                        voting_status.agree -= 1;
-                    }
-                    if voted_value == 2 {
-                        voting_status.disagree -= 1;
-                    }
-
-                    if voted_value == 3 {
-                        voting_status.neutral -= 1;
-                    }
+            voting_status = self._count_member_vote(voting_status, proposal_index, step, caller, value)?;
+            self.member_voted_by.remove((caller, proposal_index, step));
+            let mut weight: u128 = self._get_voting_weight(proposal_index, step, caller);
 
-                    self.member_voted
-                        .insert((Self::env().caller(), proposal_index, step), &value);
-                } else {
-                    // Could not re-vote.
-                    return Err(Error::NotAllowRevoting);
+            // Vote for delegators who have not voted directly.
+            for delegator in self.delegators.get((step, caller)).unwrap_or_default() {
+                let voted_value: u8 = self.get_member_voted(delegator, proposal_index, step).0;
+                let voted_by: Option<AccountId> = self.member_voted_by.get((delegator, proposal_index, step));
+                if voted_value != 0 && voted_by != Some(caller) {
+                    continue;
                 }
-            } else {
-                self.member_voted
-                    .insert((Self::env().caller(), proposal_index, step), &value);
-            }
-
-            if value == 1 {
-                voting_status.agree += 1;
-            }
-            if value == 2 {
-                voting_status.disagree += 1;
-            }
-
-            if value == 3 {
-                voting_status.neutral += 1;
+                voting_status = self._count_member_vote(voting_status, proposal_index, step, delegator, value)?;
+                self.member_voted_by.insert((delegator, proposal_index, step), &caller);
+                weight = weight.saturating_add(self._get_voting_weight(proposal_index, step, delegator));
             }
 
             // Update proposal voting status
             self.proposal_voting_status
                 .insert((proposal_index, step), &voting_status);
 
+            self.env().emit_event(Voted {
+                proposal_index,
+                step_index: step,
+                voter: caller,
+                value,
+                weight,
+            });
+
+            // Open the next step once this step passes.
+            if self.workflow_mode == WorkflowMode::Sequential
+                && !self.step_passed_at.contains((proposal_index, step))
+                && self._is_passed_step(proposal_index, step, voting_status)
+            {
+                self.step_passed_at.insert((proposal_index, step), &Self::env().block_timestamp());
+                self.env().emit_event(StepPassed {
+                    proposal_index,
+                    step_index: step,
+                });
+            }
+
             Ok(())
         }
         
         // A proposal can be executed only if that proposal qualifies all workflow steps
         #[ink(message)]
         pub fn execute_proposal(&mut self, proposal_index: u32) -> Result<(), Error> {
-            // Check the selected proposal index.
-            let num_proposals: u32 = self.proposals.len() as u32;
-            if proposal_index >= num_proposals {
-                return Err(Error::ProposalIndexOutOfBound);
+            if !self.status {
+                return Err(Error::DaoPaused);
             }
 
-            let proposal = &self.proposals[proposal_index as usize];
+            // Check the selected proposal index.
+            let mut proposal: Proposal = self._get_proposal(proposal_index)?;
             let current_timestamp: Timestamp = Self::env().block_timestamp();
 
+            // Check the proposal status
+            match self._get_proposal_status(&proposal) {
+                ProposalStatus::Executed => return Err(Error::ProposalHasExecuted),
+                ProposalStatus::Cancelled => return Err(Error::ProposalHasCancelled),
+                ProposalStatus::Expired => return Err(Error::ProposalHasExpired),
+                ProposalStatus::Rejected if proposal.status == ProposalStatus::Rejected => {
+                    return Err(Error::ProposalHasRejected);
+                }
+                _ => {}
+            }
+
             // Check time contraints
             if current_timestamp < proposal.start_date {
                 return Err(Error::VotingHasNotStarted);
             }
             // If DAO allows revoting, a proposal can not be early executed
             // If a proposal does not allow early execute, this proposal can be executed when the voting time ends.
-            if self.allow_revoting || !proposal.allow_early_executed {
-                if current_timestamp < proposal.end_date {
-                    return Err(Error::VotingHasNotEnd);
-                }
+            if (self.allow_revoting || !proposal.allow_early_executed) && current_timestamp < proposal.end_date {
+                return Err(Error::VotingHasNotEnd);
             }
 
             // Check voting status
-            let mut allow_executed: bool = true;
-            let mut i: u8 = 0;
-            let steps_len: u8 = self.steps.len() as u8;
-            loop {
-                if i >= steps_len {
-                    break;
-                }
-                let step: &Step = &self.steps[i as usize];
-                let voting_status: ProposalVoting = self
-                    .proposal_voting_status
-                    .get((proposal_index, i))
-                    .unwrap_or_default();
-                let mut quorum = step.quorum;
-                let mut threshold = step.threshold;
-
-                // If a Workflow step has it own settings.
-                if step.use_default_settings {
-                    quorum = self.global_voting_quorum;
-                    threshold = self.global_voting_threshold;
-                }
-
-                // Check conditions to execute.
-                if !self._is_allow_executed(i, voting_status, threshold, quorum) {
-                    allow_executed = false;
-                    break;
-                }
-                i += 1;
-            }
+            let allow_executed: bool = self._is_passed_all_steps(proposal_index, self.get_steps_voting_status(proposal_index));
 
             // Executed here
             if allow_executed {
+                // Proposals with milestones are paid per milestone.
+                if !self.proposal_milestones.get(proposal_index).unwrap_or_default().is_empty() {
+                    return Err(Error::ProposalHasMilestones);
+                }
+
                 let mut amount: u128 = proposal.payment_amount_crypto;
                 // Transfer token
                 if proposal.use_fiat {
-                    // Diadata here
-                    let option_price: Option<(u64, u128)> = self.oracle.get_latest_price(proposal.cryto_fiat_key.clone());
-                    let mut fetch_price_success = true;
-                    let mut latest_price: u128 = 0;
-                    match option_price {
-                        Some((_, price128)) => latest_price = price128,
-                        None => fetch_price_success = false
-                    }
-                    
-                    if !fetch_price_success {
-                        return Err(Error::CouldNotGetOraclePrice);
-                    }
-                    // Formula: tzero_amount = payment_amount_fiat * 10^18 * 10^12 / latest_price 
-                    // To avoid overflow using: tzero_amount = payment_amount * 10^18 / (latest_price/10^12)
-                    // TZERO & AZERO have decimals is 12
-                    // price128 has decimals is 18
-                    amount = (proposal.payment_amount_fiat as u128).checked_mul(10_u128.pow(18)).unwrap_or_default();
-                    latest_price = latest_price.checked_div(10_u128.pow(12)).unwrap_or_default();
-                    amount = amount.checked_div(latest_price).unwrap_or_default();
-                    // Need to check again later.
-                    if amount == 0 {
-                        return Err(Error::ZeroSendingAmount);
-                    }
-                }
-                // Check balance conditions.
-                if self.env().balance() < amount {
-                    return Err(Error::NotEnoughBalance);
+                    amount = self._fiat_to_crypto_amount(proposal.cryto_fiat_key.clone(), proposal.token, proposal.payment_amount_fiat)?;
                 }
 
-                // Transfer a token amount to the beneficiary
-                if self.env().transfer(proposal.to, amount).is_err() {
-                    panic!("error transferring")
-                }
+                self._spend_program_budget(proposal.program_index, proposal_index, proposal.token, amount)?;
+                self._payout(proposal.token, proposal.to, amount)?;
+
+                self.env().emit_event(ProposalExecuted {
+                    proposal_index,
+                    to: proposal.to,
+                    token: proposal.token,
+                    amount,
+                });
 
                 // Update the proposal status.
-                self.proposals[proposal_index as usize].executed = true; 
+                proposal.executed = true;
+                proposal.status = ProposalStatus::Executed;
+                self.proposals.insert(proposal_index, &proposal);
+                // A fiat payout below its reservation returns the rest to the program budget.
+                self._release_program_budget(proposal.program_index, proposal_index);
+            } else if current_timestamp > proposal.end_date {
+                // Voting has ended without qualifying all workflow steps.
+                proposal.status = ProposalStatus::Rejected;
+                self.proposals.insert(proposal_index, &proposal);
+                self._release_program_budget(proposal.program_index, proposal_index);
+                self.env().emit_event(ProposalRejected {
+                    proposal_index,
+                });
             }
 
             Ok(())
         }
 
-
-        // Only the DAO admin can add normal members.
+        // The proposer can withdraw a proposal until it has passed.
         #[ink(message)]
-        pub fn add_normal_member(&mut self, new_member: AccountId) -> Result<(), Error> {
+        pub fn withdraw_proposal(&mut self, proposal_index: u32) -> Result<(), Error> {
+            if !self.status {
+                return Err(Error::DaoPaused);
+            }
+
+            let proposal: Proposal = self._get_proposal(proposal_index)?;
+
             let caller = Self::env().caller();
-            if caller != self.admin {
-                return Err(Error::NotAdmin);
+            if caller != proposal.proposer {
+                return Err(Error::NotProposer);
             }
-            // Check a normal member existed or not.
-            if self.normal_members.contains(&new_member) {
-                return Err(Error::NormalMemberExisted);
+
+            match self._get_proposal_status(&proposal) {
+                ProposalStatus::Pending | ProposalStatus::Active => {}
+                _ => return Err(Error::InvalidProposalStatus),
             }
-            // Add a normal member
-            self.normal_members.push(new_member);
 
+            self._cancel_proposal(proposal, caller);
             Ok(())
         }
 
-         // Only the DAO admin can remove normal members.
+        // The admin or a ProgramManager can cancel any proposal which has not been executed, e.g. a spam proposal.
         #[ink(message)]
-        pub fn remove_normal_member(&mut self, old_member: AccountId) -> Result<(), Error> {
+        pub fn cancel_proposal(&mut self, proposal_index: u32) -> Result<(), Error> {
             let caller = Self::env().caller();
-            if caller != self.admin {
-                return Err(Error::NotAdmin);
+            self._check_role(Role::ProgramManager)?;
+
+            let proposal: Proposal = self._get_proposal(proposal_index)?;
+
+            match self._get_proposal_status(&proposal) {
+                ProposalStatus::Executed | ProposalStatus::Cancelled => return Err(Error::InvalidProposalStatus),
+                _ => {}
             }
-            // Add normal member
-            self.normal_members.retain(|&x| x != old_member);
+
+            self._cancel_proposal(proposal, caller);
             Ok(())
         }
 
-
-        // Only the DAO admin can add whitelisted contributors.
+        // Anyone can return the program budget reserved by a rejected or expired proposal.
         #[ink(message)]
-        pub fn add_whitelisted_contributor(&mut self, new_contributor: AccountId) -> Result<(), Error> {
-            let caller = Self::env().caller();
-            if caller != self.admin {
-                return Err(Error::NotAdmin);
-            }
-            // Check contributor exist
-            if self.whitelisted_contributors.contains(&new_contributor) {
-                return Err(Error::ContributorExisted);
+        pub fn release_proposal_budget(&mut self, proposal_index: u32) -> Result<(), Error> {
+            let proposal: Proposal = self._get_proposal(proposal_index)?;
+
+            match self._get_proposal_status(&proposal) {
+                ProposalStatus::Rejected | ProposalStatus::Expired => {}
+                _ => return Err(Error::InvalidProposalStatus),
             }
-            // Add contributor
-            self.whitelisted_contributors.push(new_contributor);
 
+            self._release_program_budget(proposal.program_index, proposal_index);
             Ok(())
         }
 
-        // Only the DAO admin can remove whitelisted contributors.
+        // Only the DAO admin or a ProgramManager can switch between parallel and sequential workflow steps.
         #[ink(message)]
-        pub fn remove_whitelisted_contributor(&mut self, old_member: AccountId) -> Result<(), Error> {
-            let caller = Self::env().caller();
-            if caller != self.admin {
-                return Err(Error::NotAdmin);
-            }
-            // remove contributor
-            self.whitelisted_contributors.retain(|&x| x != old_member);
+        pub fn set_workflow_mode(&mut self, workflow_mode: WorkflowMode) -> Result<(), Error> {
+            self._check_role(Role::ProgramManager)?;
+
+            self.workflow_mode = workflow_mode;
+            self.env().emit_event(WorkflowModeChanged {
+                workflow_mode,
+            });
             Ok(())
         }
 
+        // Only the DAO admin or a TreasuryManager can set guards on oracle prices used by fiat payouts.
+        // max_price_age: in milliseconds, max_price_deviation: in basis points, 0 disables a guard.
+        #[ink(message)]
+        pub fn set_price_guards(&mut self, max_price_age: u64, max_price_deviation: u32) -> Result<(), Error> {
+            self._check_role(Role::TreasuryManager)?;
+
+            self.max_price_age.set(&max_price_age);
+            self.max_price_deviation.set(&max_price_deviation);
+            self.env().emit_event(PriceGuardsChanged {
+                max_price_age,
+                max_price_deviation,
+            });
+            Ok(())
+        }
 
-        // Only the DAO admin can add workflow step members.
+        // Only the DAO admin or a TreasuryManager can set the decimals of a payout token used by fiat payouts.
+        // The zero token address is the native token.
         #[ink(message)]
-        pub fn add_step_members(
-            &mut self,
-            step_index: u8,
-            new_step_member: AccountId,
-        ) -> Result<(), Error> {
-            let caller = Self::env().caller();
-            if caller != self.admin {
-                return Err(Error::NotAdmin);
+        pub fn set_token_decimals(&mut self, token: AccountId, decimals: u8) -> Result<(), Error> {
+            self._check_role(Role::TreasuryManager)?;
+
+            self.token_decimals.insert(token, &decimals);
+            self.env().emit_event(TokenDecimalsChanged {
+                token,
+                decimals,
+            });
+            Ok(())
+        }
+
+        // Only the DAO admin or a TreasuryManager can set the decimals of oracle prices of a crypto/fiat pair.
+        #[ink(message)]
+        pub fn set_price_decimals(&mut self, cryto_fiat_key: String, decimals: u8) -> Result<(), Error> {
+            self._check_role(Role::TreasuryManager)?;
+
+            self.price_decimals.insert(cryto_fiat_key.clone(), &decimals);
+            self.env().emit_event(PriceDecimalsChanged {
+                cryto_fiat_key,
+                decimals,
+            });
+            Ok(())
+        }
+
+        // Only the DAO admin or a TreasuryManager can settle fiat payouts at a time-weighted average price.
+        // twap_window: in milliseconds, 0 settles at the spot price.
+        #[ink(message)]
+        pub fn set_twap_window(&mut self, twap_window: u64) -> Result<(), Error> {
+            self._check_role(Role::TreasuryManager)?;
+
+            self.twap_window.set(&twap_window);
+            self.env().emit_event(TwapWindowChanged {
+                twap_window,
+            });
+            Ok(())
+        }
+
+        // Only the DAO admin or a ProgramManager can set the execution period of passed proposals.
+        #[ink(message)]
+        pub fn set_execution_period(&mut self, execution_period: u64) -> Result<(), Error> {
+            self._check_role(Role::ProgramManager)?;
+
+            self.execution_period = execution_period;
+            self.env().emit_event(ExecutionPeriodChanged {
+                execution_period,
+            });
+            Ok(())
+        }
+
+
+        // The proposer can split the proposal payment into milestones before voting starts.
+        // Milestone amounts use the proposal payment unit and can not exceed the payment amount.
+        #[ink(message)]
+        pub fn add_milestone(&mut self, proposal_index: u32, title: String, amount: u128, due_date: u64) -> Result<(), Error> {
+            if !self.status {
+                return Err(Error::DaoPaused);
             }
 
-            let step_members: &mut Vec<AccountId> = &mut self.step_members[step_index as usize];
+            // Check the selected proposal index.
+            let proposal: Proposal = self._get_proposal(proposal_index)?;
 
-            if step_members.contains(&new_step_member) {
-                return Err(Error::StepMemberExisted);
+            if Self::env().caller() != proposal.proposer {
+                return Err(Error::NotProposer);
+            }
+
+            if proposal.status == ProposalStatus::Cancelled {
+                return Err(Error::ProposalHasCancelled);
+            }
+
+            if proposal.start_date <= Self::env().block_timestamp() {
+                return Err(Error::VotingHasStarted);
             }
 
-            step_members.push(new_step_member);
+            Self::_check_text(&title, "")?;
 
-            self.step_members[step_index as usize] = step_members.to_vec();
+            let mut milestones: Vec<Milestone> = self.proposal_milestones.get(proposal_index).unwrap_or_default();
+            if milestones.len() >= u8::MAX as usize {
+                return Err(Error::MilestoneIndexOutOfBound);
+            }
+
+            // Check the total milestone amount
+            let mut payment_amount: u128 = proposal.payment_amount_crypto;
+            if proposal.use_fiat {
+                payment_amount = proposal.payment_amount_fiat;
+            }
+            let total_amount: u128 = milestones
+                .iter()
+                .map(|m| m.amount)
+                .sum::<u128>()
+                .checked_add(amount)
+                .ok_or(Error::ArithmeticOverflow)?;
+            if total_amount > payment_amount {
+                return Err(Error::MilestoneAmountExceeded);
+            }
+
+            let milestone_index: u8 = milestones.len() as u8;
+            milestones.push(Milestone {
+                milestone_index,
+                title,
+                amount,
+                due_date,
+                state: MilestoneState::Pending,
+                deliverable: String::from("")
+            });
+            self.proposal_milestones.insert(proposal_index, &milestones);
+
+            self.env().emit_event(MilestoneAdded {
+                proposal_index,
+                milestone_index,
+                amount,
+                due_date,
+            });
 
             Ok(())
         }
 
-        // Only the DAO admin can remove workflow step members.
+        // The grantee submits milestone deliverables once the proposal has passed all workflow steps.
         #[ink(message)]
-        pub fn remove_step_members(&mut self, step_index: u8, old_step_member: AccountId) -> Result<(), Error> {
+        pub fn submit_milestone(&mut self, proposal_index: u32, milestone_index: u8, deliverable: String) -> Result<(), Error> {
+            if !self.status {
+                return Err(Error::DaoPaused);
+            }
+
+            let mut milestones: Vec<Milestone> = self._get_proposal_milestones(proposal_index, milestone_index)?;
+
             let caller = Self::env().caller();
-            if caller != self.admin {
-                return Err(Error::NotAdmin);
+            let proposal: Proposal = self._get_proposal(proposal_index)?;
+            if caller != proposal.to && caller != proposal.proposer {
+                return Err(Error::NotGrantee);
             }
 
-            let step_members: &mut Vec<AccountId> = &mut self.step_members[step_index as usize];
+            if !self._is_proposal_passed(&proposal) {
+                return Err(Error::ProposalHasNotPassed);
+            }
 
-            step_members.retain(|&x| x != old_step_member);
+            Self::_check_text("", &deliverable)?;
 
-            self.step_members[step_index as usize] = step_members.to_vec();
-            Ok(())
-        }
+            let milestone: &mut Milestone = &mut milestones[milestone_index as usize];
+            if milestone.state != MilestoneState::Pending {
+                return Err(Error::MilestoneHasSubmitted);
+            }
 
-        // Get functions
+            milestone.state = MilestoneState::Submitted;
+            milestone.deliverable = deliverable;
+            self.proposal_milestones.insert(proposal_index, &milestones);
 
-        #[ink(message)]
-        pub fn get_proposal(&self, proposal_index: u32) -> Option<Proposal> {
-            let num_proposals: u32 = self.proposals.len() as u32;
-            if num_proposals <= proposal_index {
-                return None;
-            } 
-            let proposal = &self.proposals[proposal_index as usize];
-            Some(proposal.clone())
-        }
+            self.env().emit_event(MilestoneSubmitted {
+                proposal_index,
+                milestone_index,
+                submitter: caller,
+            });
 
-        #[ink(message)]
-        pub fn get_program(&self, program_index: u32) -> Option<Program> {
-            let num_programs: u32 = self.programs.len() as u32;
-            if num_programs <= program_index {
-                return None;
-            } 
-            let program = &self.programs[program_index as usize];
-            Some(program.clone())
+            Ok(())
         }
 
+        // Step members approve a submitted milestone.
+        // Value: 1 - agree, 2 - disagree, 3 - abstain
         #[ink(message)]
-        pub fn get_programs(&self) -> Vec<Program> {
-            self.programs.clone()
+        pub fn milestone_voting(&mut self, proposal_index: u32, milestone_index: u8, step: u8, value: u8) -> Result<(), Error> {
+            if !self.status {
+                return Err(Error::DaoPaused);
+            }
+
+            // Check the voting value
+            if !Vec::from([1,2,3]).contains(&value) {
+                return Err(Error::IncorrectVotingOption);
+            }
+
+            let milestones: Vec<Milestone> = self._get_proposal_milestones(proposal_index, milestone_index)?;
+
+            // Caller must be a step member
+            let caller = Self::env().caller();
+            if !self._is_allow_vote(step, caller) {
+                return Err(Error::NotAllowVoting);
+            }
+
+            if milestones[milestone_index as usize].state != MilestoneState::Submitted {
+                return Err(Error::MilestoneNotSubmitted);
+            }
+
+            if self._get_proposal(proposal_index)?.status == ProposalStatus::Cancelled {
+                return Err(Error::ProposalHasCancelled);
+            }
+
+            let voting_status: ProposalVoting = self
+                .milestone_voting_status
+                .get((proposal_index, milestone_index, step))
+                .unwrap_or_default();
+
+            let voted_value: u8 = self
+                .member_milestone_voted
+                .get((caller, proposal_index, milestone_index, step))
+                .unwrap_or_default();
+            let voted_weight: u128 = self
+                .member_milestone_voted_weight
+                .get((caller, proposal_index, milestone_index, step))
+                .unwrap_or_default();
+
+            let weight: u128 = self._get_voting_weight(proposal_index, step, caller);
+            let voting_status: ProposalVoting = self._count_vote(voting_status, voted_value, voted_weight, value, weight)?;
+
+            self.member_milestone_voted
+                .insert((caller, proposal_index, milestone_index, step), &value);
+            self.member_milestone_voted_weight
+                .insert((caller, proposal_index, milestone_index, step), &weight);
+            self.milestone_voting_status
+                .insert((proposal_index, milestone_index, step), &voting_status);
+
+            self.env().emit_event(MilestoneVoted {
+                proposal_index,
+                milestone_index,
+                step_index: step,
+                voter: caller,
+                value,
+                weight,
+            });
+
+            Ok(())
         }
 
+        // A submitted milestone is paid once it qualifies all workflow steps.
+        // The proposal is executed when all of its milestones are paid.
         #[ink(message)]
-        pub fn get_program_proposals(&self, program_index: u32) -> Option<Vec<Proposal>> {
-            let num_programs: u32 = self.programs.len() as u32;
-            if num_programs <= program_index {
-                return None;
-            } 
-            let proposal_indexes: &Vec<u32> = &self.program_to_proposals[program_index as usize];
-            let mut proposals: Vec<Proposal> = Vec::new();
-            let mut i: u32 = 0;
-            loop {
-                if i >= (proposal_indexes.len() as u32) {
-                    break;
+        pub fn execute_milestone(&mut self, proposal_index: u32, milestone_index: u8) -> Result<(), Error> {
+            if !self.status {
+                return Err(Error::DaoPaused);
+            }
+
+            let mut milestones: Vec<Milestone> = self._get_proposal_milestones(proposal_index, milestone_index)?;
+
+            if milestones[milestone_index as usize].state != MilestoneState::Submitted {
+                return Err(Error::MilestoneNotSubmitted);
+            }
+
+            let mut proposal: Proposal = self._get_proposal(proposal_index)?;
+            if proposal.status == ProposalStatus::Cancelled {
+                return Err(Error::ProposalHasCancelled);
+            }
+
+            // Check milestone voting status
+            let allow_executed: bool = self._is_passed_all_steps(proposal_index, self.get_milestone_voting_status(proposal_index, milestone_index));
+
+            if allow_executed {
+                let mut amount: u128 = milestones[milestone_index as usize].amount;
+                if proposal.use_fiat {
+                    amount = self._fiat_to_crypto_amount(proposal.cryto_fiat_key.clone(), proposal.token, amount)?;
                 }
 
-                proposals.push(self.proposals[proposal_indexes[i as usize] as usize].clone());
-                i += 1;
+                self._spend_program_budget(proposal.program_index, proposal_index, proposal.token, amount)?;
+                self._payout(proposal.token, proposal.to, amount)?;
+
+                self.env().emit_event(MilestonePaid {
+                    proposal_index,
+                    milestone_index,
+                    to: proposal.to,
+                    token: proposal.token,
+                    amount,
+                });
+
+                // Update the milestone & proposal status.
+                milestones[milestone_index as usize].state = MilestoneState::Paid;
+                if milestones.iter().all(|m| m.state == MilestoneState::Paid) {
+                    proposal.executed = true;
+                    proposal.status = ProposalStatus::Executed;
+                    self.proposals.insert(proposal_index, &proposal);
+                    self._release_program_budget(proposal.program_index, proposal_index);
+                }
+                self.proposal_milestones.insert(proposal_index, &milestones);
             }
 
-            Some(proposals)
+            Ok(())
         }
 
+        // The DAO owner, admin or a Pauser can pause a DAO during an incident.
+        // Funding, proposals, voting and execution are disabled while paused.
         #[ink(message)]
-        pub fn get_steps_voting_status(&self, proposal_index: u32) -> Vec<ProposalVoting> {
-            let mut step_votings: Vec<ProposalVoting> = Vec::new();
-            let steps_len: u8 = self.steps.len() as u8;
-            let mut i: u8 = 0;
-            loop {
-                if i >= steps_len {
-                    break;
-                }
-                let step_voting: ProposalVoting = self
-                    .proposal_voting_status
-                    .get((proposal_index, i))
-                    .unwrap_or_default();
-                step_votings.push(step_voting);
-                i += 1;
+        pub fn pause(&mut self) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            if caller != self.owner {
+                self._check_role(Role::Pauser)?;
             }
-            step_votings
+
+            self.status = false;
+
+            self.env().emit_event(Paused { account: caller });
+            Ok(())
         }
 
+        // The DAO owner, admin or a Pauser can unpause a DAO.
         #[ink(message)]
-        pub fn get_step_members(&self) -> Vec<Vec<AccountId>> {
-            self.step_members.clone()
+        pub fn unpause(&mut self) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            if caller != self.owner {
+                self._check_role(Role::Pauser)?;
+            }
+
+            self.status = true;
+
+            self.env().emit_event(Unpaused { account: caller });
+            Ok(())
         }
 
+        // The DAO owner can start transferring the ownership,
+        // the new owner becomes the owner once it accepts.
         #[ink(message)]
-        pub fn get_normal_members(&self) -> Vec<AccountId> {
-            self.normal_members.clone()
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), Error> {
+            if Self::env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+
+            self.pending_owner.set(&Some(new_owner));
+            self.env().emit_event(OwnershipTransferStarted {
+                previous_owner: self.owner,
+                new_owner,
+            });
+            Ok(())
         }
 
         #[ink(message)]
-        pub fn get_contract_balance(&self) -> u128 {
-            self.env().balance()
+        pub fn accept_ownership(&mut self) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            if self.pending_owner.get().flatten() != Some(caller) {
+                return Err(Error::NotPendingOwner);
+            }
+
+            let previous_owner: AccountId = self.owner;
+            self.owner = caller;
+            self.pending_owner.set(&None);
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner: caller,
+            });
+            Ok(())
         }
 
+        // The DAO admin or owner can start transferring the admin role,
+        // so the owner can replace a departing or compromised admin.
+        // The new admin becomes the admin once it accepts.
         #[ink(message)]
-        pub fn get_price(&self, key: String) -> Option<(u64, u128)> {
-            self.oracle.get_latest_price(key)
+        pub fn transfer_admin(&mut self, new_admin: AccountId) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            if caller != self.admin && caller != self.owner {
+                return Err(Error::NotAdminOrOwner);
+            }
+
+            self.pending_admin.set(&Some(new_admin));
+            self.env().emit_event(AdminTransferStarted {
+                previous_admin: self.admin,
+                new_admin,
+            });
+            Ok(())
         }
 
         #[ink(message)]
-        pub fn get_whitelisted_contributors(&self) -> Vec<AccountId> {
-            self.whitelisted_contributors.clone()
+        pub fn accept_admin(&mut self) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            if self.pending_admin.get().flatten() != Some(caller) {
+                return Err(Error::NotPendingAdmin);
+            }
+
+            let previous_admin: AccountId = self.admin;
+            self.admin = caller;
+            self.pending_admin.set(&None);
+            self.env().emit_event(AdminTransferred {
+                previous_admin,
+                new_admin: caller,
+            });
+            Ok(())
         }
 
+        // Only the DAO admin can grant roles.
         #[ink(message)]
-        pub fn get_member_voted(&self, member: AccountId, proposal_index: u32, step_index: u8) -> u8 {
-            let voted_value: u8 = self
-            .member_voted
-            .get((member, proposal_index, step_index))
-            .unwrap_or_default();
-            voted_value
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            if caller != self.admin {
+                return Err(Error::NotAdmin);
+            }
+
+            if !self._add_member(MemberGroup::RoleMember(role), account) {
+                return Err(Error::RoleExisted);
+            }
+
+            self.env().emit_event(RoleGranted { role, account });
+            Ok(())
         }
 
+        // Only the DAO admin can revoke roles.
         #[ink(message)]
-        pub fn get_proposal_payment_amount_from_oracle(&self, proposal_index: u32) -> Result<(u128, u128), Error> {
-            let num_proposals: u32 = self.proposals.len() as u32;
-            if proposal_index >= num_proposals {
-                return Err(Error::ProposalIndexOutOfBound);
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            if caller != self.admin {
+                return Err(Error::NotAdmin);
             }
-            
-            let proposal = &self.proposals[proposal_index as usize];
-            if !proposal.use_fiat {
-                return Ok((0, 0))
+
+            if self._remove_member(MemberGroup::RoleMember(role), account) {
+                self.env().emit_event(RoleRevoked { role, account });
             }
-            
-            let option_price: Option<(u64, u128)> = self.oracle.get_latest_price(proposal.cryto_fiat_key.clone());
-            let mut fetch_price_success = true;
-            let mut latest_price: u128 = 0;
-            match option_price {
-                Some((_, price128)) => latest_price = price128,
-                None => fetch_price_success = false
+            Ok(())
+        }
+
+        // The DAO owner (the DAOFactory for DAOs created by the factory) can upgrade the DAO code.
+        // `migrate` must be called after an upgrade to bring the storage to the new layout.
+        #[ink(message)]
+        pub fn upgrade(&mut self, code_hash: Hash) -> Result<(), Error> {
+            if Self::env().caller() != self.owner {
+                return Err(Error::NotOwner);
             }
-            
-            if !fetch_price_success {
-                return Err(Error::CouldNotGetOraclePrice);
+
+            if self.env().set_code_hash(&code_hash).is_err() {
+                return Err(Error::UpgradeFailed);
             }
 
-            let mut amount: u128 = (proposal.payment_amount_fiat as u128).checked_mul(10_u128.pow(18)).unwrap_or_default();
-            latest_price = latest_price.checked_div(10_u128.pow(12)).unwrap_or_default();
-            amount = amount.checked_div(latest_price).unwrap_or_default();
+            self.env().emit_event(Upgraded { code_hash });
+            Ok(())
+        }
+
+        // Migrate the storage from its current version to the version of this code.
+        // Nothing happens if the storage is up to date.
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<(), Error> {
+            if Self::env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
 
+            let from_version: u32 = self.storage_version;
+            if from_version >= STORAGE_VERSION {
+                return Ok(());
+            }
 
-            Ok((amount, self.env().balance()))
+            // Migration steps run in order.
+            // Version 2: programs moved from a vector in the root storage to a mapping,
+            // fiat payment amounts changed from u32 whole units to u128 minor units.
+            if self.storage_version < 2 {
+                let legacy_programs: Vec<Program> = core::mem::take(&mut self.legacy_programs);
+                for program in legacy_programs.iter() {
+                    self.programs.insert(program.program_index, program);
+                }
+                self.num_programs.set(&(legacy_programs.len() as u32));
+                self._migrate_v1_proposals()?;
+                self.storage_version = 2;
+            }
 
+            self.storage_version = STORAGE_VERSION;
+            self.env().emit_event(Migrated {
+                from_version,
+                to_version: STORAGE_VERSION,
+            });
+            Ok(())
         }
 
-        // Private functions
+        // Re-encode the proposals of storage version 1 with fiat amounts in minor units.
+        // Milestones of a fiat proposal share the unit of its payment amount.
+        fn _migrate_v1_proposals(&mut self) -> Result<(), Error> {
+            let proposals_key = self.proposals.key();
+            let fiat_unit: u128 = 10_u128.pow(FIAT_DECIMALS as u32);
+
+            for proposal_index in 0..self.num_proposals {
+                let legacy_proposal: ProposalV1 = match ink::env::get_contract_storage(&(&proposals_key, proposal_index)) {
+                    Ok(Some(legacy_proposal)) => legacy_proposal,
+                    _ => continue,
+                };
+
+                let proposal = Proposal {
+                    program_index: legacy_proposal.program_index,
+                    proposal_index: legacy_proposal.proposal_index,
+                    proposer: legacy_proposal.proposer,
+                    title: legacy_proposal.title,
+                    description: legacy_proposal.description,
+                    start_date: legacy_proposal.start_date,
+                    end_date: legacy_proposal.end_date,
+                    use_fiat: legacy_proposal.use_fiat,
+                    payment_amount_fiat: legacy_proposal.payment_amount_fiat as u128 * fiat_unit,
+                    cryto_fiat_key: legacy_proposal.cryto_fiat_key,
+                    payment_amount_crypto: legacy_proposal.payment_amount_crypto,
+                    token: legacy_proposal.token,
+                    to: legacy_proposal.to,
+                    allow_early_executed: legacy_proposal.allow_early_executed,
+                    executed: legacy_proposal.executed,
+                    status: legacy_proposal.status,
+                };
+                self.proposals.insert(proposal_index, &proposal);
 
-        // This function will traversal all workflow steps 
-        // and check whether each step has a qualify quorum and threshold.
-        fn _is_allow_executed(
-            &self,
+                if proposal.use_fiat {
+                    if let Some(mut milestones) = self.proposal_milestones.get(proposal_index) {
+                        for milestone in milestones.iter_mut() {
+                            milestone.amount = milestone.amount.checked_mul(fiat_unit).ok_or(Error::ArithmeticOverflow)?;
+                        }
+                        self.proposal_milestones.insert(proposal_index, &milestones);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        // Only the DAO admin or a MembershipManager can set voting weights of step members.
+        // Weights are used by steps in the FixedWeight voting mode.
+        #[ink(message)]
+        pub fn set_member_weight(&mut self, step_index: u8, member: AccountId, weight: u128) -> Result<(), Error> {
+            self._check_role(Role::MembershipManager)?;
+
+            if step_index as usize >= self.steps.len() {
+                return Err(Error::StepIndexOutOfBound);
+            }
+
+            // Keep the total weight of step members up to date.
+            if self._is_member(MemberGroup::StepMember(step_index), member) {
+                let total_weight: u128 = self.step_total_weights.get(step_index).unwrap_or_default()
+                    - self.member_weights.get((step_index, member)).unwrap_or(1);
+                let total_weight: u128 = total_weight.checked_add(weight).ok_or(Error::ArithmeticOverflow)?;
+                self.step_total_weights.insert(step_index, &total_weight);
+            }
+            self.member_weights.insert((step_index, member), &weight);
+
+            self.env().emit_event(MemberWeightChanged {
+                step_index,
+                member,
+                weight,
+            });
+            Ok(())
+        }
+
+        // A step member can hand the vote in a step to another member of the same step.
+        // Delegation can not be chained: a delegate can not delegate its vote.
+        #[ink(message)]
+        pub fn delegate(&mut self, step_index: u8, to: AccountId) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            if step_index as usize >= self.steps.len() {
+                return Err(Error::StepIndexOutOfBound);
+            }
+
+            if !self._is_allow_vote(step_index, caller) {
+                return Err(Error::NotAllowVoting);
+            }
+
+            if self.delegations.contains((step_index, caller)) {
+                return Err(Error::AlreadyDelegated);
+            }
+
+            if to == caller
+                || !self._is_allow_vote(step_index, to)
+                || self.delegations.contains((step_index, to))
+                || self.delegators.contains((step_index, caller))
+            {
+                return Err(Error::InvalidDelegate);
+            }
+
+            self.delegations.insert((step_index, caller), &to);
+            let mut delegators: Vec<AccountId> = self.delegators.get((step_index, to)).unwrap_or_default();
+            delegators.push(caller);
+            self.delegators.insert((step_index, to), &delegators);
+
+            self.env().emit_event(Delegated {
+                step_index,
+                delegator: caller,
+                delegate: to,
+            });
+            Ok(())
+        }
+
+        // Take back the vote in a step from the delegate.
+        // Votes already cast by the delegate are kept.
+        #[ink(message)]
+        pub fn undelegate(&mut self, step_index: u8) -> Result<(), Error> {
+            let caller = Self::env().caller();
+            let delegate: AccountId = match self.delegations.get((step_index, caller)) {
+                Some(delegate) => delegate,
+                None => return Err(Error::NotDelegated),
+            };
+
+            self._remove_delegation(step_index, caller, delegate);
+
+            self.env().emit_event(Undelegated {
+                step_index,
+                delegator: caller,
+                delegate,
+            });
+            Ok(())
+        }
+
+        // Only the DAO admin or a MembershipManager can add normal members.
+        #[ink(message)]
+        pub fn add_normal_member(&mut self, new_member: AccountId) -> Result<(), Error> {
+            self._check_role(Role::MembershipManager)?;
+            // Add a normal member if not existed.
+            if !self._add_member(MemberGroup::NormalMember, new_member) {
+                return Err(Error::NormalMemberExisted);
+            }
+
+            self.env().emit_event(NormalMemberAdded { member: new_member });
+
+            Ok(())
+        }
+
+        // Only the DAO admin or a MembershipManager can remove normal members.
+        #[ink(message)]
+        pub fn remove_normal_member(&mut self, old_member: AccountId) -> Result<(), Error> {
+            self._check_role(Role::MembershipManager)?;
+            // Remove normal member
+            if self._remove_member(MemberGroup::NormalMember, old_member) {
+                self.env().emit_event(NormalMemberRemoved { member: old_member });
+            }
+            Ok(())
+        }
+
+
+        // Only the DAO admin or a MembershipManager can add whitelisted contributors.
+        #[ink(message)]
+        pub fn add_whitelisted_contributor(&mut self, new_contributor: AccountId) -> Result<(), Error> {
+            self._check_role(Role::MembershipManager)?;
+            // Add contributor if not existed.
+            if !self._add_member(MemberGroup::WhitelistedContributor, new_contributor) {
+                return Err(Error::ContributorExisted);
+            }
+
+            self.env().emit_event(ContributorAdded { contributor: new_contributor });
+
+            Ok(())
+        }
+
+        // Only the DAO admin or a MembershipManager can remove whitelisted contributors.
+        #[ink(message)]
+        pub fn remove_whitelisted_contributor(&mut self, old_member: AccountId) -> Result<(), Error> {
+            self._check_role(Role::MembershipManager)?;
+            // remove contributor
+            if self._remove_member(MemberGroup::WhitelistedContributor, old_member) {
+                self.env().emit_event(ContributorRemoved { contributor: old_member });
+            }
+            Ok(())
+        }
+
+
+        // Only the DAO admin or a MembershipManager can add workflow step members.
+        #[ink(message)]
+        pub fn add_step_members(
+            &mut self,
             step_index: u8,
-            voting_status: ProposalVoting,
-            threshold: u8,
-            quorum: u8,
-        ) -> bool {
-            let mut is_allow_executed: bool = false;
-            let agree: u32 = voting_status.agree;
-            let disagree: u32 = voting_status.disagree;
-            let neutral: u32 = voting_status.neutral;
-            let members: &Vec<AccountId> = &self.step_members[step_index as usize];
-            let member_len: u8 = members.len() as u8;
-            if member_len > 0 {
-                let total_member_votings = agree + disagree + neutral;
-                if total_member_votings * 100 >= (quorum * member_len) as u32
-                    && agree * 100 >= total_member_votings * (threshold as u32)
-                {
-                    is_allow_executed = true;
-                } else {
-                    is_allow_executed = false;
+            new_step_member: AccountId,
+        ) -> Result<(), Error> {
+            self._check_role(Role::MembershipManager)?;
+
+            if step_index as usize >= self.steps.len() {
+                return Err(Error::StepIndexOutOfBound);
+            }
+
+            if !self._add_step_member(step_index, new_step_member) {
+                return Err(Error::StepMemberExisted);
+            }
+
+            self.env().emit_event(StepMemberAdded {
+                step_index,
+                member: new_step_member,
+            });
+
+            Ok(())
+        }
+
+        // Only the DAO admin or a MembershipManager can remove workflow step members.
+        #[ink(message)]
+        pub fn remove_step_members(&mut self, step_index: u8, old_step_member: AccountId) -> Result<(), Error> {
+            self._check_role(Role::MembershipManager)?;
+
+            if step_index as usize >= self.steps.len() {
+                return Err(Error::StepIndexOutOfBound);
+            }
+
+            if self._remove_step_member(step_index, old_step_member) {
+                // Clear delegations from and to the removed member.
+                if let Some(delegate) = self.delegations.get((step_index, old_step_member)) {
+                    self._remove_delegation(step_index, old_step_member, delegate);
                 }
+                for delegator in self.delegators.get((step_index, old_step_member)).unwrap_or_default() {
+                    self._remove_delegation(step_index, delegator, old_step_member);
+                }
+
+                self.env().emit_event(StepMemberRemoved {
+                    step_index,
+                    member: old_step_member,
+                });
             }
-            is_allow_executed
+            Ok(())
         }
 
-        // Whether the caller is a workflow step member or not
-        fn _is_allow_vote(&self, step_index: u8, caller: AccountId) -> bool {
-            let mut is_allow_vote: bool = false;
-            let step_members: &Vec<AccountId> = &self.step_members[step_index as usize];
-            if step_members.contains(&caller) {
-                is_allow_vote = true;
+        // Get functions
+
+        #[ink(message)]
+        pub fn get_proposal(&self, proposal_index: u32) -> Option<Proposal> {
+            self.proposals.get(proposal_index)
+        }
+
+        // Current status of a proposal, computed from its stored status, voting and time constraints.
+        #[ink(message)]
+        pub fn get_proposal_status(&self, proposal_index: u32) -> Option<ProposalStatus> {
+            self.proposals
+                .get(proposal_index)
+                .map(|proposal| self._get_proposal_status(&proposal))
+        }
+
+        // (max price age, max price deviation)
+        #[ink(message)]
+        pub fn get_price_guards(&self) -> (u64, u32) {
+            (
+                self.max_price_age.get().unwrap_or_default(),
+                self.max_price_deviation.get().unwrap_or_default(),
+            )
+        }
+
+        #[ink(message)]
+        pub fn get_settled_price(&self, key: String) -> Option<u128> {
+            self.settled_prices.get(key)
+        }
+
+        // Decimals used to scale fiat payouts in a token.
+        #[ink(message)]
+        pub fn get_token_decimals(&self, token: AccountId) -> Result<u8, Error> {
+            self._get_token_decimals(token)
+        }
+
+        #[ink(message)]
+        pub fn get_twap_window(&self) -> u64 {
+            self.twap_window.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_price_decimals(&self, cryto_fiat_key: String) -> u8 {
+            self._get_price_decimals(&cryto_fiat_key)
+        }
+
+        // (pending owner, pending admin)
+        #[ink(message)]
+        pub fn get_pending_transfers(&self) -> (Option<AccountId>, Option<AccountId>) {
+            (
+                self.pending_owner.get().flatten(),
+                self.pending_admin.get().flatten(),
+            )
+        }
+
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u32 {
+            self.storage_version
+        }
+
+        #[ink(message)]
+        pub fn get_code_hash(&self) -> Hash {
+            self.env().own_code_hash().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_execution_period(&self) -> u64 {
+            self.execution_period
+        }
+
+        #[ink(message)]
+        pub fn get_workflow_mode(&self) -> WorkflowMode {
+            self.workflow_mode
+        }
+
+        // Time when a step of a proposal passed in the sequential workflow mode.
+        #[ink(message)]
+        pub fn get_step_passed_at(&self, proposal_index: u32, step_index: u8) -> Option<Timestamp> {
+            self.step_passed_at.get((proposal_index, step_index))
+        }
+
+        #[ink(message)]
+        pub fn get_program(&self, program_index: u32) -> Option<Program> {
+            self.programs.get(program_index)
+        }
+
+        #[ink(message)]
+        pub fn get_program_state(&self, program_index: u32) -> ProgramState {
+            self.program_states.get(program_index).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_program_budget(&self, program_index: u32) -> Option<ProgramBudget> {
+            self.program_budgets.get(program_index)
+        }
+
+        // Treasury amount of a token allocated to program budgets and not paid yet.
+        #[ink(message)]
+        pub fn get_allocated_budget(&self, token: AccountId) -> u128 {
+            self.allocated_budgets.get(token).unwrap_or_default()
+        }
+
+        // Program budget reserved by a proposal and not paid yet.
+        #[ink(message)]
+        pub fn get_proposal_commitment(&self, proposal_index: u32) -> u128 {
+            self.proposal_commitments.get(proposal_index).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_programs(&self) -> Vec<Program> {
+            (0..self._num_programs())
+                .filter_map(|i| self.programs.get(i))
+                .collect()
+        }
+
+        // A page of programs, the limit is capped by MAX_PAGE_LIMIT.
+        #[ink(message)]
+        pub fn get_programs_paginated(&self, offset: u32, limit: u32) -> Vec<Program> {
+            let num_programs: u32 = self._num_programs();
+            (offset.min(num_programs)..offset.saturating_add(limit.min(MAX_PAGE_LIMIT)).min(num_programs))
+                .filter_map(|i| self.programs.get(i))
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn get_num_programs(&self) -> u32 {
+            self._num_programs()
+        }
+
+        // Proposals matching the filter, offset and limit count matching proposals only.
+        #[ink(message)]
+        pub fn get_proposals(&self, filter: ProposalFilter, offset: u32, limit: u32) -> Vec<Proposal> {
+            let limit: u32 = limit.min(MAX_PAGE_LIMIT);
+            let num_proposals: u32 = match filter.program_index {
+                Some(program_index) => self.num_program_proposals.get(program_index).unwrap_or_default(),
+                None => self.num_proposals,
+            };
+            let proposal_at = |i: u32| -> Option<Proposal> {
+                let proposal_index: u32 = match filter.program_index {
+                    Some(program_index) => self.program_to_proposals.get((program_index, i))?,
+                    None => i,
+                };
+                self.proposals.get(proposal_index)
+            };
+
+            // Without other filters, only proposals of the page are read.
+            if filter.proposer.is_none() && filter.executed.is_none() && filter.status.is_none() {
+                return (offset.min(num_proposals)..offset.saturating_add(limit).min(num_proposals))
+                    .filter_map(proposal_at)
+                    .collect();
             }
 
-            is_allow_vote
+            (0..num_proposals)
+                .filter_map(proposal_at)
+                .filter(|proposal| self._is_matched_proposal(proposal, &filter))
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect()
         }
-        
-    }
 
-    #[cfg(test)]
-    mod tests {
-        /// Imports all the definitions from the outer scope so we can use them here.
-        use super::*;
-        use ink::env::{
-            test::{set_caller}
-        };
+        #[ink(message)]
+        pub fn get_num_proposals(&self) -> u32 {
+            self.num_proposals
+        }
 
-        fn get_mock_accounts() -> (AccountId, AccountId, AccountId, AccountId, AccountId, AccountId) {
-            let admin_acc = AccountId::from([0x01; 32]);
-            let step1_member = AccountId::from([0x02; 32]);
-            let step2_member = AccountId::from([0x03; 32]);
-            let whitelisted_contributor = AccountId::from([0x04; 32]);
-            let normal_member = AccountId::from([0x05; 32]);
-            let oracle_contract = AccountId::from([0x00; 32]);
-            (admin_acc, step1_member, step2_member, whitelisted_contributor, normal_member, oracle_contract)
+        #[ink(message)]
+        pub fn get_program_proposals(&self, program_index: u32) -> Option<Vec<Proposal>> {
+            if self._num_programs() <= program_index {
+                return None;
+            }
+            let num_program_proposals: u32 = self.num_program_proposals.get(program_index).unwrap_or_default();
+            let proposals: Vec<Proposal> = (0..num_program_proposals)
+                .filter_map(|i| self.program_to_proposals.get((program_index, i)))
+                .filter_map(|proposal_index| self.proposals.get(proposal_index))
+                .collect();
+
+            Some(proposals)
         }
 
-        fn get_mock_proposal_params() -> (AccountId, AccountId) {
-            let token = AccountId::from([0x06; 32]);
-            let recipient = AccountId::from([0x07; 32]);
-            (token, recipient)
+        #[ink(message)]
+        pub fn get_steps_voting_status(&self, proposal_index: u32) -> Vec<ProposalVoting> {
+            let mut step_votings: Vec<ProposalVoting> = Vec::new();
+            let steps_len: u8 = self.steps.len() as u8;
+            let mut i: u8 = 0;
+            loop {
+                if i >= steps_len {
+                    break;
+                }
+                let step_voting: ProposalVoting = self
+                    .proposal_voting_status
+                    .get((proposal_index, i))
+                    .unwrap_or_default();
+                step_votings.push(step_voting);
+                i += 1;
+            }
+            step_votings
         }
 
+        #[ink(message)]
+        pub fn get_proposal_revisions(&self, proposal_index: u32) -> Vec<ProposalRevision> {
+            self.proposal_revisions.get(proposal_index).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_milestones(&self, proposal_index: u32) -> Vec<Milestone> {
+            self.proposal_milestones.get(proposal_index).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_milestone_voting_status(&self, proposal_index: u32, milestone_index: u8) -> Vec<ProposalVoting> {
+            let mut step_votings: Vec<ProposalVoting> = Vec::new();
+            let steps_len: u8 = self.steps.len() as u8;
+            let mut i: u8 = 0;
+            loop {
+                if i >= steps_len {
+                    break;
+                }
+                let step_voting: ProposalVoting = self
+                    .milestone_voting_status
+                    .get((proposal_index, milestone_index, i))
+                    .unwrap_or_default();
+                step_votings.push(step_voting);
+                i += 1;
+            }
+            step_votings
+        }
+
+        #[ink(message)]
+        pub fn get_member_milestone_voted(&self, member: AccountId, proposal_index: u32, milestone_index: u8, step_index: u8) -> u8 {
+            self.member_milestone_voted
+                .get((member, proposal_index, milestone_index, step_index))
+                .unwrap_or_default()
+        }
+
+        // Voting weight of a step member for a proposal.
+        #[ink(message)]
+        pub fn get_voting_weight(&self, proposal_index: u32, step_index: u8, member: AccountId) -> u128 {
+            if step_index as usize >= self.steps.len() {
+                return 0;
+            }
+            self._get_voting_weight(proposal_index, step_index, member)
+        }
+
+        #[ink(message)]
+        pub fn get_step_members(&self) -> Vec<Vec<AccountId>> {
+            (0..self.steps.len() as u8)
+                .map(|step_index| {
+                    let group = MemberGroup::StepMember(step_index);
+                    self._get_members(group, 0, self._num_members(group))
+                })
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn get_step_members_paginated(&self, step_index: u8, offset: u32, limit: u32) -> Vec<AccountId> {
+            self._get_members(MemberGroup::StepMember(step_index), offset, limit.min(MAX_PAGE_LIMIT))
+        }
+
+        #[ink(message)]
+        pub fn get_normal_members(&self) -> Vec<AccountId> {
+            self._get_members(MemberGroup::NormalMember, 0, self._num_members(MemberGroup::NormalMember))
+        }
+
+        #[ink(message)]
+        pub fn get_normal_members_paginated(&self, offset: u32, limit: u32) -> Vec<AccountId> {
+            self._get_members(MemberGroup::NormalMember, offset, limit.min(MAX_PAGE_LIMIT))
+        }
+
+        #[ink(message)]
+        pub fn get_contract_balance(&self) -> u128 {
+            self.env().balance()
+        }
+
+        #[ink(message)]
+        pub fn get_price(&self, key: String) -> Option<(u64, u128)> {
+            self.oracle.get_latest_price(key)
+        }
+
+        #[ink(message)]
+        pub fn get_whitelisted_contributors(&self) -> Vec<AccountId> {
+            let group = MemberGroup::WhitelistedContributor;
+            self._get_members(group, 0, self._num_members(group))
+        }
+
+        #[ink(message)]
+        pub fn get_whitelisted_contributors_paginated(&self, offset: u32, limit: u32) -> Vec<AccountId> {
+            self._get_members(MemberGroup::WhitelistedContributor, offset, limit.min(MAX_PAGE_LIMIT))
+        }
+
+        // Whether an account holds a role, the admin holds every role.
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            account == self.admin || self._is_member(MemberGroup::RoleMember(role), account)
+        }
+
+        // Accounts granted a role, without the admin.
+        #[ink(message)]
+        pub fn get_role_members(&self, role: Role, offset: u32, limit: u32) -> Vec<AccountId> {
+            self._get_members(MemberGroup::RoleMember(role), offset, limit.min(MAX_PAGE_LIMIT))
+        }
+
+        // Total amount a contributor has funded in a token.
+        // The zero token address is the native token.
+        #[ink(message)]
+        pub fn get_contribution(&self, contributor: AccountId, token: AccountId) -> u128 {
+            self.contributions.get((contributor, token)).unwrap_or_default()
+        }
+
+        // All (token, amount) contributions of a contributor.
+        #[ink(message)]
+        pub fn get_contributions(&self, contributor: AccountId) -> Vec<(AccountId, u128)> {
+            let tokens: Vec<AccountId> = self.contributor_tokens.get(contributor).unwrap_or_default();
+            let mut contributions: Vec<(AccountId, u128)> = Vec::new();
+            for token in tokens {
+                contributions.push((token, self.get_contribution(contributor, token)));
+            }
+            contributions
+        }
+
+        // Total amount funded to this DAO in a token.
+        #[ink(message)]
+        pub fn get_token_fund(&self, token: AccountId) -> u128 {
+            self.token_funds.get(token).unwrap_or_default()
+        }
+
+        // Vote value of a member and the delegate who cast it, None if the member voted directly.
+        #[ink(message)]
+        pub fn get_member_voted(&self, member: AccountId, proposal_index: u32, step_index: u8) -> (u8, Option<AccountId>) {
+            let voted_value: u8 = self
+            .member_voted
+            .get((member, proposal_index, step_index))
+            .unwrap_or_default();
+            (voted_value, self.member_voted_by.get((member, proposal_index, step_index)))
+        }
+
+        #[ink(message)]
+        pub fn get_delegate(&self, step_index: u8, member: AccountId) -> Option<AccountId> {
+            self.delegations.get((step_index, member))
+        }
+
+        #[ink(message)]
+        pub fn get_delegators(&self, step_index: u8, member: AccountId) -> Vec<AccountId> {
+            self.delegators.get((step_index, member)).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_proposal_payment_amount_from_oracle(&self, proposal_index: u32) -> Result<(u128, u128), Error> {
+            let proposal: Proposal = self._get_proposal(proposal_index)?;
+            if !proposal.use_fiat {
+                return Ok((0, 0))
+            }
+
+            let (_, amount) = self._quote_fiat_to_crypto_amount(&proposal.cryto_fiat_key, proposal.token, proposal.payment_amount_fiat)?;
+            Ok((amount, self._get_balance(proposal.token)?))
+
+        }
+
+        // Private functions
+
+        // This function will traversal all workflow steps 
+        // and check whether each step has a qualify quorum and threshold.
+        // total_weight: the total voting weight of the step members.
+        fn _is_allow_executed(
+            &self,
+            total_weight: u128,
+            voting_status: ProposalVoting,
+            threshold: u8,
+            quorum: u8,
+        ) -> bool {
+            let mut is_allow_executed: bool = false;
+            let agree: u128 = voting_status.agree;
+            let disagree: u128 = voting_status.disagree;
+            let neutral: u128 = voting_status.neutral;
+            if total_weight > 0 {
+                let total_member_votings = agree.saturating_add(disagree).saturating_add(neutral);
+                is_allow_executed = Self::_is_percent_reached(total_member_votings, total_weight, quorum)
+                    && Self::_is_percent_reached(agree, total_member_votings, threshold);
+            }
+            is_allow_executed
+        }
+
+        // Whether value * 100 >= total * percent, without overflowing on large token weights.
+        // value >= ceil(total * percent / 100) where total * percent = 100 * (total / 100) * percent + (total % 100) * percent.
+        fn _is_percent_reached(value: u128, total: u128, percent: u8) -> bool {
+            let percent: u128 = percent as u128;
+            (total / 100)
+                .checked_mul(percent)
+                .and_then(|required| required.checked_add(((total % 100) * percent).div_ceil(100)))
+                .is_some_and(|required| value >= required)
+        }
+
+        // Check whether each workflow step has a qualify quorum and threshold.
+        // step_votings: voting status of each step.
+        fn _is_passed_all_steps(&self, proposal_index: u32, step_votings: Vec<ProposalVoting>) -> bool {
+            let mut i: u8 = 0;
+            let steps_len: u8 = self.steps.len() as u8;
+            loop {
+                if i >= steps_len {
+                    break;
+                }
+                if !self._is_passed_step(proposal_index, i, step_votings[i as usize].clone()) {
+                    return false;
+                }
+                i += 1;
+            }
+            true
+        }
+
+        // Check whether a workflow step has a qualify quorum and threshold.
+        fn _is_passed_step(&self, proposal_index: u32, step_index: u8, voting_status: ProposalVoting) -> bool {
+            let step: &Step = &self.steps[step_index as usize];
+            let mut quorum = step.quorum;
+            let mut threshold = step.threshold;
+
+            // If a Workflow step has it own settings.
+            if step.use_default_settings {
+                quorum = self.global_voting_quorum;
+                threshold = self.global_voting_threshold;
+            }
+
+            let total_weight: u128 = self._get_step_total_weight(proposal_index, step_index);
+            self._is_allow_executed(total_weight, voting_status, threshold, quorum)
+        }
+
+        // In the sequential workflow mode, a step opens once all previous steps have passed
+        // (at the proposal start date for the first step), None while it is not open.
+        // Previous steps are checked against the current tallies, so a step which passed without a vote,
+        // e.g. after a member or weight change, opens the next step from the time it is first voted.
+        fn _get_step_opened_at(&self, proposal: &Proposal, step_index: u8) -> Option<Timestamp> {
+            if step_index == 0 {
+                return Some(proposal.start_date);
+            }
+
+            let step_votings: Vec<ProposalVoting> = self.get_steps_voting_status(proposal.proposal_index);
+            for i in 0..step_index {
+                if !self._is_passed_step(proposal.proposal_index, i, step_votings[i as usize].clone()) {
+                    return None;
+                }
+            }
+
+            Some(
+                self.step_passed_at
+                    .get((proposal.proposal_index, step_index - 1))
+                    .unwrap_or(Self::env().block_timestamp()),
+            )
+        }
+
+        // In the sequential workflow mode, a step is open until its voting window ends.
+        fn _is_step_active(&self, proposal: &Proposal, step_index: u8) -> bool {
+            let Some(opened_at) = self._get_step_opened_at(proposal, step_index) else {
+                return false;
+            };
+
+            let voting_window: u64 = self.steps[step_index as usize].voting_window;
+            voting_window == 0 || Self::env().block_timestamp() <= opened_at.saturating_add(voting_window)
+        }
+
+        fn _is_matched_proposal(&self, proposal: &Proposal, filter: &ProposalFilter) -> bool {
+            if filter.proposer.is_some_and(|proposer| proposer != proposal.proposer) {
+                return false;
+            }
+            if filter.executed.is_some_and(|executed| executed != proposal.executed) {
+                return false;
+            }
+            if filter.status.is_some_and(|status| status != self._get_proposal_status(proposal)) {
+                return false;
+            }
+            true
+        }
+
+        // Whether a proposal qualifies all workflow steps and its voting time constraints.
+        fn _is_proposal_passed(&self, proposal: &Proposal) -> bool {
+            self._get_proposal_status(proposal) == ProposalStatus::Passed
+        }
+
+        fn _get_proposal_status(&self, proposal: &Proposal) -> ProposalStatus {
+            let proposal_index: u32 = proposal.proposal_index;
+            match proposal.status {
+                ProposalStatus::Cancelled | ProposalStatus::Rejected | ProposalStatus::Executed => return proposal.status,
+                _ => {}
+            }
+
+            let current_timestamp: Timestamp = Self::env().block_timestamp();
+            if current_timestamp < proposal.start_date {
+                return ProposalStatus::Pending;
+            }
+
+            let allow_early_executed: bool = !self.allow_revoting && proposal.allow_early_executed;
+            if (allow_early_executed || current_timestamp >= proposal.end_date)
+                && self._is_passed_all_steps(proposal_index, self.get_steps_voting_status(proposal_index))
+            {
+                // Proposals with milestones are paid per milestone and do not expire.
+                let has_milestones: bool = !self.proposal_milestones.get(proposal_index).unwrap_or_default().is_empty();
+                if self.execution_period > 0
+                    && !has_milestones
+                    && current_timestamp > proposal.end_date.saturating_add(self.execution_period)
+                {
+                    return ProposalStatus::Expired;
+                }
+                return ProposalStatus::Passed;
+            }
+
+            if current_timestamp <= proposal.end_date {
+                return ProposalStatus::Active;
+            }
+            ProposalStatus::Rejected
+        }
+
+        fn _cancel_proposal(&mut self, mut proposal: Proposal, account: AccountId) {
+            self._release_program_budget(proposal.program_index, proposal.proposal_index);
+
+            proposal.status = ProposalStatus::Cancelled;
+            self.proposals.insert(proposal.proposal_index, &proposal);
+            self.env().emit_event(ProposalCancelled {
+                proposal_index: proposal.proposal_index,
+                account,
+            });
+        }
+
+        fn _get_program(&self, program_index: u32) -> Result<Program, Error> {
+            self.programs.get(program_index).ok_or(Error::ProgramIndexOutOfBound)
+        }
+
+        fn _num_programs(&self) -> u32 {
+            self.num_programs.get().unwrap_or_default()
+        }
+
+        fn _get_proposal(&self, proposal_index: u32) -> Result<Proposal, Error> {
+            self.proposals.get(proposal_index).ok_or(Error::ProposalIndexOutOfBound)
+        }
+
+        fn _num_members(&self, group: MemberGroup) -> u32 {
+            self.num_members.get(group).unwrap_or_default()
+        }
+
+        fn _is_member(&self, group: MemberGroup, member: AccountId) -> bool {
+            self.member_indexes.contains((group, member))
+        }
+
+        fn _check_role(&self, role: Role) -> Result<(), Error> {
+            if !self.has_role(role, Self::env().caller()) {
+                return Err(Error::MissingRole);
+            }
+            Ok(())
+        }
+
+        // Members of a group from the offset index, at most limit members.
+        fn _get_members(&self, group: MemberGroup, offset: u32, limit: u32) -> Vec<AccountId> {
+            let num_members: u32 = self._num_members(group);
+            (offset.min(num_members)..offset.saturating_add(limit).min(num_members))
+                .filter_map(|i| self.members.get((group, i)))
+                .collect()
+        }
+
+        // Add a member to a group, returns false if the member existed.
+        fn _add_member(&mut self, group: MemberGroup, member: AccountId) -> bool {
+            if self._is_member(group, member) {
+                return false;
+            }
+            let num_members: u32 = self._num_members(group);
+            self.members.insert((group, num_members), &member);
+            self.member_indexes.insert((group, member), &num_members);
+            self.num_members.insert(group, &(num_members + 1));
+            true
+        }
+
+        // Remove a member from a group, the last member takes its index.
+        // Returns false if the member did not exist.
+        fn _remove_member(&mut self, group: MemberGroup, member: AccountId) -> bool {
+            let index: u32 = match self.member_indexes.get((group, member)) {
+                Some(index) => index,
+                None => return false,
+            };
+            let last_index: u32 = self._num_members(group) - 1;
+            if index != last_index {
+                if let Some(last_member) = self.members.get((group, last_index)) {
+                    self.members.insert((group, index), &last_member);
+                    self.member_indexes.insert((group, last_member), &index);
+                }
+            }
+            self.members.remove((group, last_index));
+            self.member_indexes.remove((group, member));
+            self.num_members.insert(group, &last_index);
+            true
+        }
+
+        fn _add_step_member(&mut self, step_index: u8, member: AccountId) -> bool {
+            if !self._add_member(MemberGroup::StepMember(step_index), member) {
+                return false;
+            }
+            let total_weight: u128 = self.step_total_weights.get(step_index).unwrap_or_default();
+            let weight: u128 = self.member_weights.get((step_index, member)).unwrap_or(1);
+            self.step_total_weights.insert(step_index, &total_weight.saturating_add(weight));
+            true
+        }
+
+        fn _remove_step_member(&mut self, step_index: u8, member: AccountId) -> bool {
+            if !self._remove_member(MemberGroup::StepMember(step_index), member) {
+                return false;
+            }
+            let total_weight: u128 = self.step_total_weights.get(step_index).unwrap_or_default();
+            let weight: u128 = self.member_weights.get((step_index, member)).unwrap_or(1);
+            self.step_total_weights.insert(step_index, &(total_weight - weight));
+            true
+        }
+
+        // Voting weight of a step member according to the step voting mode.
+        fn _get_voting_weight(&self, proposal_index: u32, step_index: u8, member: AccountId) -> u128 {
+            match self.steps[step_index as usize].voting_mode {
+                VotingMode::OneMemberOneVote => 1,
+                VotingMode::FixedWeight => self.member_weights.get((step_index, member)).unwrap_or(1),
+                VotingMode::TokenWeighted => self
+                    .voting_weight_snapshots
+                    .get((proposal_index, step_index, member))
+                    .unwrap_or_default(),
+            }
+        }
+
+        // Total voting weight of a step, used to compute the quorum.
+        fn _get_step_total_weight(&self, proposal_index: u32, step_index: u8) -> u128 {
+            match self.steps[step_index as usize].voting_mode {
+                VotingMode::OneMemberOneVote => self._num_members(MemberGroup::StepMember(step_index)) as u128,
+                VotingMode::FixedWeight => self.step_total_weights.get(step_index).unwrap_or_default(),
+                VotingMode::TokenWeighted => self
+                    .step_total_weight_snapshots
+                    .get((proposal_index, step_index))
+                    .unwrap_or_default(),
+            }
+        }
+
+        // Record a member vote and apply it to the voting status of a step.
+        fn _count_member_vote(&mut self, voting_status: ProposalVoting, proposal_index: u32, step: u8, member: AccountId, value: u8) -> Result<ProposalVoting, Error> {
+            let voted_value: u8 = self
+                .member_voted
+                .get((member, proposal_index, step))
+                .unwrap_or_default();
+            let voted_weight: u128 = self
+                .member_voted_weight
+                .get((member, proposal_index, step))
+                .unwrap_or_default();
+
+            let weight: u128 = self._get_voting_weight(proposal_index, step, member);
+            let voting_status: ProposalVoting = self._count_vote(voting_status, voted_value, voted_weight, value, weight)?;
+
+            self.member_voted
+                .insert((member, proposal_index, step), &value);
+            self.member_voted_weight
+                .insert((member, proposal_index, step), &weight);
+            if voted_value == 0 {
+                let mut voters: Vec<AccountId> = self.proposal_voters.get((proposal_index, step)).unwrap_or_default();
+                voters.push(member);
+                self.proposal_voters.insert((proposal_index, step), &voters);
+            }
+            Ok(voting_status)
+        }
+
+        // Record the content hash of a created or updated proposal.
+        fn _add_proposal_revision(&mut self, proposal: &Proposal) -> ProposalRevision {
+            let mut revisions: Vec<ProposalRevision> = self.proposal_revisions.get(proposal.proposal_index).unwrap_or_default();
+
+            let content_cid: Option<Vec<u8>> = self.content_cids.get(ContentKey::Proposal(proposal.proposal_index));
+            let content_hash: [u8; 32] = self.env().hash_encoded::<ink::env::hash::Blake2x256, _>(
+                &(&proposal.title, &proposal.description, proposal.payment_amount_fiat, proposal.payment_amount_crypto, proposal.to, content_cid)
+            );
+
+            let revision = ProposalRevision {
+                revision: revisions.len() as u32,
+                content_hash: Hash::from(content_hash),
+                updated_at: Self::env().block_timestamp(),
+            };
+            revisions.push(revision);
+            self.proposal_revisions.insert(proposal.proposal_index, &revisions);
+            revision
+        }
+
+        // Clear all votes of a proposal, returns true if there was any vote.
+        // Votes are cleared for the recorded voters, including members removed from the step since.
+        fn _reset_votes(&mut self, proposal_index: u32) -> bool {
+            let mut votes_reset: bool = false;
+            for step in 0..self.steps.len() as u8 {
+                let voters: Vec<AccountId> = self.proposal_voters.get((proposal_index, step)).unwrap_or_default();
+                if voters.is_empty() {
+                    continue;
+                }
+                votes_reset = true;
+
+                for member in voters {
+                    self.member_voted.remove((member, proposal_index, step));
+                    self.member_voted_weight.remove((member, proposal_index, step));
+                    self.member_voted_by.remove((member, proposal_index, step));
+                }
+                self.proposal_voters.remove((proposal_index, step));
+                self.proposal_voting_status.insert((proposal_index, step), &ProposalVoting::default());
+                self.step_passed_at.remove((proposal_index, step));
+            }
+            votes_reset
+        }
+
+        fn _remove_delegation(&mut self, step_index: u8, delegator: AccountId, delegate: AccountId) {
+            self.delegations.remove((step_index, delegator));
+            let mut delegators: Vec<AccountId> = self.delegators.get((step_index, delegate)).unwrap_or_default();
+            delegators.retain(|&x| x != delegator);
+            if delegators.is_empty() {
+                self.delegators.remove((step_index, delegate));
+            } else {
+                self.delegators.insert((step_index, delegate), &delegators);
+            }
+        }
+
+        // Apply a member vote to the voting status of a step.
+        // voted_value, voted_weight: the previous vote of the member, value 0 if the member has not voted yet.
+        fn _count_vote(&self, mut voting_status: ProposalVoting, voted_value: u8, voted_weight: u128, value: u8, weight: u128) -> Result<ProposalVoting, Error> {
+            // If the caller voted and this DAO allows users revoting.
+            if voted_value != 0 {
+                if !self.allow_revoting {
+                    // Could not re-vote.
+                    return Err(Error::NotAllowRevoting);
+                }
+                if voted_value == value {
+                    return Err(Error::SameVotingOption);
+                }
+                if voted_value == 1 {
+                    voting_status.agree -= voted_weight;
+                }
+                if voted_value == 2 {
+                    voting_status.disagree -= voted_weight;
+                }
+
+                if voted_value == 3 {
+                    voting_status.neutral -= voted_weight;
+                }
+            }
+
+            let tally: &mut u128 = match value {
+                1 => &mut voting_status.agree,
+                2 => &mut voting_status.disagree,
+                _ => &mut voting_status.neutral,
+            };
+            *tally = tally.checked_add(weight).ok_or(Error::ArithmeticOverflow)?;
+
+            Ok(voting_status)
+        }
+
+        // Milestones of a proposal, checking both proposal and milestone indexes.
+        fn _get_proposal_milestones(&self, proposal_index: u32, milestone_index: u8) -> Result<Vec<Milestone>, Error> {
+            if proposal_index >= self.num_proposals {
+                return Err(Error::ProposalIndexOutOfBound);
+            }
+
+            let milestones: Vec<Milestone> = self.proposal_milestones.get(proposal_index).unwrap_or_default();
+            if milestone_index as usize >= milestones.len() {
+                return Err(Error::MilestoneIndexOutOfBound);
+            }
+            Ok(milestones)
+        }
+
+        // Convert a fiat amount to a token amount with the latest oracle prices
+        // and record them as the settled prices of their pairs.
+        fn _fiat_to_crypto_amount(&mut self, cryto_fiat_key: String, token: AccountId, fiat_amount: u128) -> Result<u128, Error> {
+            let (prices, amount) = self._quote_fiat_to_crypto_amount(&cryto_fiat_key, token, fiat_amount)?;
+            for (pair, price) in prices {
+                self.settled_prices.insert(pair, &price);
+            }
+            Ok(amount)
+        }
+
+        // Fetch the latest oracle prices of a pair, check them with the price guards
+        // and convert a fiat amount in minor units to token units.
+        // Returns ((pair, price) of each used oracle price, amount).
+        fn _quote_fiat_to_crypto_amount(&self, cryto_fiat_key: &str, token: AccountId, fiat_amount: u128) -> Result<(Vec<(String, u128)>, u128), Error> {
+            let prices: Vec<(String, u64, u128)> = self._get_pair_prices(cryto_fiat_key)?;
+            for (pair, price_timestamp, price) in prices.iter() {
+                self._check_price_guards(pair, *price_timestamp, *price)?;
+            }
+
+            let (latest_price, price_decimals) = match prices.as_slice() {
+                [(pair, _, price)] => (*price, self._get_price_decimals(pair)),
+                [(crypto_pair, _, crypto_price), (fiat_pair, _, fiat_price)] => (
+                    cross_price(
+                        *crypto_price,
+                        self._get_price_decimals(crypto_pair),
+                        *fiat_price,
+                        self._get_price_decimals(fiat_pair),
+                    )?,
+                    DEFAULT_PRICE_DECIMALS,
+                ),
+                _ => return Err(Error::CouldNotGetOraclePrice),
+            };
+
+            let amount: u128 = fiat_to_crypto_amount(
+                fiat_amount,
+                latest_price,
+                price_decimals,
+                self._get_token_decimals(token)?,
+            )?;
+            Ok((prices.into_iter().map(|(pair, _, price)| (pair, price)).collect(), amount))
+        }
+
+        // Latest oracle prices of a "CRYPTO/FIAT" pair as (pair, timestamp, price).
+        // The pair itself if the oracle has it, otherwise the cross rate pairs
+        // CRYPTO/CROSS_RATE_CURRENCY and FIAT/CROSS_RATE_CURRENCY.
+        fn _get_pair_prices(&self, cryto_fiat_key: &str) -> Result<Vec<(String, u64, u128)>, Error> {
+            let (crypto, fiat) = match cryto_fiat_key.split_once('/') {
+                Some((crypto, fiat)) if !crypto.is_empty() && !fiat.is_empty() && !fiat.contains('/') => (crypto, fiat),
+                _ => return Err(Error::InvalidPairKey),
+            };
+
+            if let Some((price_timestamp, price)) = self._get_oracle_price(String::from(cryto_fiat_key)) {
+                return Ok(vec![(String::from(cryto_fiat_key), price_timestamp, price)]);
+            }
+            if fiat == CROSS_RATE_CURRENCY {
+                return Err(Error::CouldNotGetOraclePrice);
+            }
+
+            let mut prices: Vec<(String, u64, u128)> = Vec::new();
+            for currency in [crypto, fiat] {
+                let pair: String = format!("{}/{}", currency, CROSS_RATE_CURRENCY);
+                match self._get_oracle_price(pair.clone()) {
+                    Some((price_timestamp, price)) => prices.push((pair, price_timestamp, price)),
+                    None => return Err(Error::CouldNotGetOraclePrice),
+                }
+            }
+            Ok(prices)
+        }
+
+        // Configured decimals of a token, else 12 for the native token,
+        // else the decimals from the PSP22 metadata of the token.
+        fn _get_token_decimals(&self, token: AccountId) -> Result<u8, Error> {
+            if let Some(decimals) = self.token_decimals.get(token) {
+                return Ok(decimals);
+            }
+            if token == AccountId::from([0x00; 32]) {
+                return Ok(NATIVE_TOKEN_DECIMALS);
+            }
+
+            let token_ref: contract_ref!(PSP22Metadata) = token.into();
+            match token_ref.call().token_decimals().try_invoke() {
+                Ok(Ok(decimals)) => Ok(decimals),
+                _ => Err(Error::UnknownTokenDecimals),
+            }
+        }
+
+        // (timestamp, price) of the latest oracle price of a pair.
+        // With a TWAP window the price is the oracle TWAP over the window.
+        fn _get_oracle_price(&self, pair: String) -> Option<(u64, u128)> {
+            // Diadata here
+            let (price_timestamp, price) = self.oracle.get_latest_price(pair.clone())?;
+            let twap_window: u64 = self.twap_window.get().unwrap_or_default();
+            if twap_window == 0 {
+                return Some((price_timestamp, price));
+            }
+            Some((price_timestamp, self.oracle.get_twap(pair, twap_window)?))
+        }
+
+        fn _get_price_decimals(&self, cryto_fiat_key: &String) -> u8 {
+            self.price_decimals.get(cryto_fiat_key).unwrap_or(DEFAULT_PRICE_DECIMALS)
+        }
+
+        // Reject a price older than the max price age
+        // or deviating from the last settled price of the pair more than the max price deviation.
+        fn _check_price_guards(&self, cryto_fiat_key: &String, price_timestamp: u64, price: u128) -> Result<(), Error> {
+            let max_price_age: u64 = self.max_price_age.get().unwrap_or_default();
+            if max_price_age > 0 && Self::env().block_timestamp().saturating_sub(price_timestamp) > max_price_age {
+                return Err(Error::StalePrice);
+            }
+
+            let max_price_deviation: u32 = self.max_price_deviation.get().unwrap_or_default();
+            if max_price_deviation > 0 {
+                if let Some(settled_price) = self.settled_prices.get(cryto_fiat_key) {
+                    let deviation: u128 = price.abs_diff(settled_price);
+                    if deviation.saturating_mul(10_000) > settled_price.saturating_mul(max_price_deviation as u128) {
+                        return Err(Error::PriceDeviation);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        // Inline titles and descriptions are limited in length.
+        fn _check_text(title: &str, description: &str) -> Result<(), Error> {
+            if title.len() > MAX_TITLE_LENGTH {
+                return Err(Error::TitleTooLong);
+            }
+            if description.len() > MAX_DESCRIPTION_LENGTH {
+                return Err(Error::DescriptionTooLong);
+            }
+            Ok(())
+        }
+
+        fn _check_social_accounts(social_accounts: &[String]) -> Result<(), Error> {
+            if social_accounts.len() > MAX_SOCIAL_ACCOUNTS {
+                return Err(Error::TooManySocialAccounts);
+            }
+            if social_accounts.iter().any(|account| account.len() > MAX_SOCIAL_ACCOUNT_LENGTH) {
+                return Err(Error::SocialAccountTooLong);
+            }
+            Ok(())
+        }
+
+        // A proposal pays a non-zero amount, in fiat or crypto units, to a non-zero beneficiary.
+        fn _check_payment(use_fiat: bool, payment_amount_fiat: u128, payment_amount_crypto: u128, to: AccountId) -> Result<(), Error> {
+            if (use_fiat && payment_amount_fiat == 0) || (!use_fiat && payment_amount_crypto == 0) {
+                return Err(Error::ZeroPaymentAmount);
+            }
+            if to == AccountId::from([0x00; 32]) {
+                return Err(Error::ZeroBeneficiary);
+            }
+            Ok(())
+        }
+
+        // Programs can be changed until they are archived.
+        fn _check_program_editable(&self, program_index: u32) -> Result<(), Error> {
+            if self._num_programs() <= program_index {
+                return Err(Error::ProgramIndexOutOfBound);
+            }
+            if self.get_program_state(program_index) == ProgramState::Archived {
+                return Err(Error::ProgramArchived);
+            }
+            Ok(())
+        }
+
+        // Reserve the payment amount of a proposal in its program budget,
+        // a fiat amount is converted at the current oracle price.
+        fn _commit_proposal_budget(&mut self, proposal: &Proposal) -> Result<(), Error> {
+            if !self.program_budgets.contains(proposal.program_index) {
+                return Ok(());
+            }
+
+            let mut amount: u128 = proposal.payment_amount_crypto;
+            if proposal.use_fiat {
+                amount = self._quote_fiat_to_crypto_amount(&proposal.cryto_fiat_key, proposal.token, proposal.payment_amount_fiat)?.1;
+            }
+            self._commit_program_budget(proposal.program_index, proposal.proposal_index, amount)
+        }
+
+        // Reserve a part of the program budget for a proposal.
+        fn _commit_program_budget(&mut self, program_index: u32, proposal_index: u32, amount: u128) -> Result<(), Error> {
+            if let Some(mut budget) = self.program_budgets.get(program_index) {
+                if amount > budget.amount.saturating_sub(budget.committed) {
+                    return Err(Error::ExceedProgramBudget);
+                }
+                budget.committed = budget.committed.checked_add(amount).ok_or(Error::ArithmeticOverflow)?;
+                self.program_budgets.insert(program_index, &budget);
+
+                let commitment: u128 = self.proposal_commitments.get(proposal_index).unwrap_or_default();
+                let commitment: u128 = commitment.checked_add(amount).ok_or(Error::ArithmeticOverflow)?;
+                self.proposal_commitments.insert(proposal_index, &commitment);
+            }
+            Ok(())
+        }
+
+        // Return the unpaid reservation of a proposal to its program budget.
+        // The reservation of an archived program returns to the treasury.
+        fn _release_program_budget(&mut self, program_index: u32, proposal_index: u32) {
+            let commitment: u128 = self.proposal_commitments.get(proposal_index).unwrap_or_default();
+            self.proposal_commitments.remove(proposal_index);
+
+            if let Some(mut budget) = self.program_budgets.get(program_index) {
+                budget.committed = budget.committed.saturating_sub(commitment);
+                if self.get_program_state(program_index) == ProgramState::Archived {
+                    budget.amount = budget.amount.saturating_sub(commitment);
+                    let allocated: u128 = self.allocated_budgets.get(budget.token).unwrap_or_default();
+                    self.allocated_budgets.insert(budget.token, &allocated.saturating_sub(commitment));
+                }
+                self.program_budgets.insert(program_index, &budget);
+            }
+        }
+
+        // Account a payout of a proposal against its reservation in the program budget.
+        // A fiat payout converted above its reservation reserves the difference first.
+        // Programs without a budget can only spend the treasury balance not allocated to budgets.
+        fn _spend_program_budget(&mut self, program_index: u32, proposal_index: u32, token: AccountId, amount: u128) -> Result<(), Error> {
+            let commitment: u128 = self.proposal_commitments.get(proposal_index).unwrap_or_default();
+            if amount > commitment {
+                self._commit_program_budget(program_index, proposal_index, amount - commitment)?;
+            }
+
+            let allocated: u128 = self.allocated_budgets.get(token).unwrap_or_default();
+            match self.program_budgets.get(program_index) {
+                Some(mut budget) => {
+                    budget.paid = budget.paid.checked_add(amount).ok_or(Error::ArithmeticOverflow)?;
+                    self.program_budgets.insert(program_index, &budget);
+                    self.allocated_budgets.insert(token, &allocated.saturating_sub(amount));
+
+                    let commitment: u128 = self.proposal_commitments.get(proposal_index).unwrap_or_default();
+                    self.proposal_commitments.insert(proposal_index, &commitment.saturating_sub(amount));
+                }
+                None => {
+                    if self._get_balance(token)?.saturating_sub(allocated) < amount {
+                        return Err(Error::NotEnoughBalance);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        // Transfer a token amount from the DAO treasury to the beneficiary.
+        // A zero token address means the native token, otherwise a PSP22 token.
+        fn _payout(&self, token: AccountId, to: AccountId, amount: u128) -> Result<(), Error> {
+            // Check balance conditions.
+            if self._get_balance(token)? < amount {
+                return Err(Error::NotEnoughBalance);
+            }
+
+            if token == AccountId::from([0x00; 32]) {
+                if self.env().transfer(to, amount).is_err() {
+                    return Err(Error::TransferFailed);
+                }
+            } else {
+                self._transfer_psp22(token, to, amount)?;
+            }
+            Ok(())
+        }
+
+        // Update the treasury ledger after a contribution and emit a Funded event.
+        fn _add_contribution(&mut self, contributor: AccountId, token: AccountId, amount: u128) -> Result<(), Error> {
+            let contributed: u128 = self.contributions.get((contributor, token)).unwrap_or_default();
+            let funded: u128 = self.token_funds.get(token).unwrap_or_default();
+            let (Some(new_contributed), Some(new_funded)) = (contributed.checked_add(amount), funded.checked_add(amount)) else {
+                return Err(Error::ArithmeticOverflow);
+            };
+
+            if contributed == 0 {
+                let mut tokens: Vec<AccountId> = self.contributor_tokens.get(contributor).unwrap_or_default();
+                tokens.push(token);
+                self.contributor_tokens.insert(contributor, &tokens);
+            }
+            self.contributions.insert((contributor, token), &new_contributed);
+            self.token_funds.insert(token, &new_funded);
+
+            self.env().emit_event(Funded {
+                contributor,
+                token,
+                amount,
+            });
+            Ok(())
+        }
+
+        // Native token balance of this DAO if the token address is zero,
+        // otherwise its PSP22 token balance.
+        fn _get_balance(&self, token: AccountId) -> Result<u128, Error> {
+            if token == AccountId::from([0x00; 32]) {
+                return Ok(self.env().balance());
+            }
+            self._get_token_balance(token, self.env().account_id())
+        }
+
+        // PSP22 token balance of an account.
+        // Any failure of the cross-contract call, e.g. a token address which is not a PSP22 contract,
+        // is returned as TokenBalanceFailed.
+        fn _get_token_balance(&self, token: AccountId, owner: AccountId) -> Result<u128, Error> {
+            let token_ref: contract_ref!(PSP22) = token.into();
+            match token_ref.call().balance_of(owner).try_invoke() {
+                Ok(Ok(balance)) => Ok(balance),
+                _ => Err(Error::TokenBalanceFailed),
+            }
+        }
+
+        // Call the PSP22 transfer message of a token contract.
+        // Any failure of the cross-contract call is returned as TokenTransferFailed.
+        fn _transfer_psp22(&self, token: AccountId, to: AccountId, amount: u128) -> Result<(), Error> {
+            let mut token_ref: contract_ref!(PSP22) = token.into();
+            match token_ref.call_mut().transfer(to, amount, Vec::new()).try_invoke() {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(Error::TokenTransferFailed),
+            }
+        }
+
+        // Whether the caller is a workflow step member or not
+        fn _is_allow_vote(&self, step_index: u8, caller: AccountId) -> bool {
+            self._is_member(MemberGroup::StepMember(step_index), caller)
+        }
+        
+    }
+
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink::env::{
+            hash::{Blake2x256, CryptoHash, HashOutput},
+            test::{
+                get_contract_storage_rw, recorded_events, set_block_timestamp, set_caller, set_value_transferred,
+                EmittedEvent,
+            }
+        };
+        use ink::primitives::Clear;
+
+        type Event = <Dao as ::ink::reflect::ContractEventBase>::Type;
+
+        struct PrefixedValue<'a, 'b, T> {
+            pub prefix: &'a [u8],
+            pub value: &'b T,
+        }
+
+        impl<X> scale::Encode for PrefixedValue<'_, '_, X>
+        where
+            X: scale::Encode,
+        {
+            #[inline]
+            fn size_hint(&self) -> usize {
+                self.prefix.size_hint() + self.value.size_hint()
+            }
+
+            #[inline]
+            fn encode_to<T: scale::Output + ?Sized>(&self, dest: &mut T) {
+                self.prefix.encode_to(dest);
+                self.value.encode_to(dest);
+            }
+        }
+
+        fn encoded_into_hash<T>(entity: &T) -> Hash
+        where
+            T: scale::Encode,
+        {
+            let mut result: Hash = Hash::CLEAR_HASH;
+            let len_result: usize = result.as_ref().len();
+            let encoded: Vec<u8> = entity.encode();
+            let len_encoded: usize = encoded.len();
+            if len_encoded <= len_result {
+                result.as_mut()[..len_encoded].copy_from_slice(&encoded);
+                return result;
+            }
+            let mut hash_output: [u8; 32] =
+                <<Blake2x256 as HashOutput>::Type as Default>::default();
+            <Blake2x256 as CryptoHash>::hash(&encoded, &mut hash_output);
+            let copy_len: usize = core::cmp::min(hash_output.len(), len_result);
+            result.as_mut()[0..copy_len].copy_from_slice(&hash_output[0..copy_len]);
+            result
+        }
+
+        fn assert_topics(event: &EmittedEvent, expected_topics: &[Hash]) {
+            assert_eq!(event.topics.len(), expected_topics.len(), "encountered invalid number of topics");
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic: Hash = <Hash as scale::Decode>::decode(&mut &actual_topic[..])
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(&topic, expected_topic, "encountered invalid topic at {n}");
+            }
+        }
+
+        fn assert_proposal_created_event(
+            event: &EmittedEvent,
+            expected_program_index: u32,
+            expected_proposal_index: u32,
+            expected_proposer: AccountId,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::ProposalCreated(ProposalCreated {
+                program_index,
+                proposal_index,
+                proposer,
+            }) = decoded_event
+            {
+                assert_eq!(program_index, expected_program_index, "encountered invalid ProposalCreated.program_index");
+                assert_eq!(proposal_index, expected_proposal_index, "encountered invalid ProposalCreated.proposal_index");
+                assert_eq!(proposer, expected_proposer, "encountered invalid ProposalCreated.proposer");
+            } else {
+                panic!("encountered unexpected event kind: expected a ProposalCreated event")
+            }
+
+            assert_topics(event, &[
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"",
+                    value: b"Dao::ProposalCreated",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Dao::ProposalCreated::program_index",
+                    value: &expected_program_index,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Dao::ProposalCreated::proposal_index",
+                    value: &expected_proposal_index,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Dao::ProposalCreated::proposer",
+                    value: &expected_proposer,
+                }),
+            ]);
+        }
+
+        fn assert_voted_event(
+            event: &EmittedEvent,
+            expected_proposal_index: u32,
+            expected_step_index: u8,
+            expected_voter: AccountId,
+            expected_value: u8,
+            expected_weight: u128,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Voted(Voted {
+                proposal_index,
+                step_index,
+                voter,
+                value,
+                weight,
+            }) = decoded_event
+            {
+                assert_eq!(proposal_index, expected_proposal_index, "encountered invalid Voted.proposal_index");
+                assert_eq!(step_index, expected_step_index, "encountered invalid Voted.step_index");
+                assert_eq!(voter, expected_voter, "encountered invalid Voted.voter");
+                assert_eq!(value, expected_value, "encountered invalid Voted.value");
+                assert_eq!(weight, expected_weight, "encountered invalid Voted.weight");
+            } else {
+                panic!("encountered unexpected event kind: expected a Voted event")
+            }
+
+            assert_topics(event, &[
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"",
+                    value: b"Dao::Voted",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Dao::Voted::proposal_index",
+                    value: &expected_proposal_index,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Dao::Voted::voter",
+                    value: &expected_voter,
+                }),
+            ]);
+        }
+
+        fn assert_proposal_executed_event(
+            event: &EmittedEvent,
+            expected_proposal_index: u32,
+            expected_to: AccountId,
+            expected_amount: u128,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::ProposalExecuted(ProposalExecuted {
+                proposal_index,
+                to,
+                amount,
+                ..
+            }) = decoded_event
+            {
+                assert_eq!(proposal_index, expected_proposal_index, "encountered invalid ProposalExecuted.proposal_index");
+                assert_eq!(to, expected_to, "encountered invalid ProposalExecuted.to");
+                assert_eq!(amount, expected_amount, "encountered invalid ProposalExecuted.amount");
+            } else {
+                panic!("encountered unexpected event kind: expected a ProposalExecuted event")
+            }
+
+            assert_topics(event, &[
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"",
+                    value: b"Dao::ProposalExecuted",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Dao::ProposalExecuted::proposal_index",
+                    value: &expected_proposal_index,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Dao::ProposalExecuted::to",
+                    value: &expected_to,
+                }),
+            ]);
+        }
+
+        fn get_mock_accounts() -> (AccountId, AccountId, AccountId, AccountId, AccountId, AccountId) {
+            let admin_acc = AccountId::from([0x01; 32]);
+            let step1_member = AccountId::from([0x02; 32]);
+            let step2_member = AccountId::from([0x03; 32]);
+            let whitelisted_contributor = AccountId::from([0x04; 32]);
+            let normal_member = AccountId::from([0x05; 32]);
+            let oracle_contract = AccountId::from([0x00; 32]);
+            (admin_acc, step1_member, step2_member, whitelisted_contributor, normal_member, oracle_contract)
+        }
+
+        fn get_mock_proposal_params() -> (AccountId, AccountId) {
+            // Zero token address: proposals are paid in the native token.
+            let token = AccountId::from([0x00; 32]);
+            let recipient = AccountId::from([0x07; 32]);
+            (token, recipient)
+        }
+
+
+        fn init_dao() -> Dao {
+            let mock_accounts = get_mock_accounts();
+            Dao::new(
+                mock_accounts.5,
+                mock_accounts.0,
+                "Name".to_string(),
+                "Description".to_string(),
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                vec!["twitter".to_string(), "facebook".to_string()],
+                vec![
+                    Step {
+                        step_index: 0,
+                        title: "Step 1".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                    Step {
+                        step_index: 1,
+                        title: "Step 2".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                ],
+                vec![vec![mock_accounts.1], vec![mock_accounts.2]],
+                vec![mock_accounts.3],
+                100,
+                100,
+                vec![mock_accounts.4],
+                false,
+                false,
+            ).unwrap()
+        }
+
+        #[ink::test]
+        fn test_init_dao() {
+            let dao = init_dao();
+            let info = dao.get_info();
+            assert_eq!(info.2, "Name".to_string());
+            assert_eq!(info.8.len(), 2);
+        }
+
+        #[ink::test]
+        fn test_init_dao_fail() {
+            let mock_accounts = get_mock_accounts();
+            let new_dao = |name: String, social_accounts: Vec<String>| Dao::new(
+                mock_accounts.5,
+                mock_accounts.0,
+                name,
+                "Description".to_string(),
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                social_accounts,
+                vec![],
+                vec![],
+                vec![],
+                100,
+                100,
+                vec![],
+                false,
+                false,
+            );
+            assert_eq!(new_dao("N".repeat(MAX_TITLE_LENGTH + 1), vec![]).err(), Some(Error::TitleTooLong));
+            assert_eq!(new_dao("Name".to_string(), vec!["twitter".to_string(); MAX_SOCIAL_ACCOUNTS + 1]).err(), Some(Error::TooManySocialAccounts));
+            assert_eq!(new_dao("Name".to_string(), vec!["t".repeat(MAX_SOCIAL_ACCOUNT_LENGTH + 1)]).err(), Some(Error::SocialAccountTooLong));
+            assert!(new_dao("Name".to_string(), vec![]).is_ok());
+        }
+
+        #[ink::test]
+        fn test_create_program_success() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+
+            let program = dao.get_program(0);
+            assert!(program.is_some(), "Fail to create new program");
+        }
+
+        #[ink::test]
+        fn test_create_program_fail() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+            set_caller::<Environment>(mock_accounts.1);
+
+            let result: Result<(), Error> = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+
+            assert!(result.is_err(), "Only admin can create programs");
+
+            set_caller::<Environment>(mock_accounts.0);
+            let result: Result<(), Error> = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                1000,
+                999,
+                None
+            );
+            assert_eq!(result, Err(Error::InvalidProgramDates));
+            assert_eq!(dao.get_programs().len(), 0);
+        }
+
+        #[ink::test]
+        fn test_create_proposal_validation() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program("Program Title".to_string(), "Program Description".to_string(), 100, 1000, None);
+
+            set_block_timestamp::<Environment>(100);
+            set_caller::<Environment>(mock_accounts.4);
+            let create_proposal = |dao: &mut Dao, program_index: u32, start_date: u64, end_date: u64, use_fiat: bool, amount: u128, to: AccountId| dao.create_proposal(
+                program_index,
+                "Title".to_string(),
+                "Description".to_string(),
+                start_date,
+                end_date,
+                use_fiat,
+                amount,
+                "AZERO/USD".to_string(),
+                amount,
+                mock_param.0,
+                to,
+                true
+            );
+
+            // The index equal to the number of programs is out of bound.
+            assert_eq!(create_proposal(&mut dao, 1, 100, 1000, false, 200, mock_param.1), Err(Error::ProgramIndexOutOfBound));
+            assert_eq!(create_proposal(&mut dao, 0, 500, 499, false, 200, mock_param.1), Err(Error::InvalidProposalDates));
+            assert_eq!(create_proposal(&mut dao, 0, 99, 1000, false, 200, mock_param.1), Err(Error::ProposalOutsideProgram));
+            assert_eq!(create_proposal(&mut dao, 0, 100, 1001, false, 200, mock_param.1), Err(Error::ProposalOutsideProgram));
+            assert_eq!(create_proposal(&mut dao, 0, 100, 1000, false, 0, mock_param.1), Err(Error::ZeroPaymentAmount));
+            assert_eq!(create_proposal(&mut dao, 0, 100, 1000, true, 0, mock_param.1), Err(Error::ZeroPaymentAmount));
+            assert_eq!(create_proposal(&mut dao, 0, 100, 1000, false, 200, AccountId::from([0x00; 32])), Err(Error::ZeroBeneficiary));
+            assert_eq!(dao.get_num_proposals(), 0);
+
+            assert_eq!(create_proposal(&mut dao, 0, 200, 1000, false, 200, mock_param.1), Ok(()));
+            assert_eq!(dao.update_proposal(0, "Title".to_string(), "Description".to_string(), 0, 0, mock_param.1), Err(Error::ZeroPaymentAmount));
+            assert_eq!(dao.update_proposal(0, "Title".to_string(), "Description".to_string(), 0, 200, AccountId::from([0x00; 32])), Err(Error::ZeroBeneficiary));
+        }
+        
+        #[ink::test] 
+        fn test_create_proposal() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            set_caller::<Environment>(mock_accounts.0);
+            // Create program
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+
+            let program = dao.get_program(0);
+            assert!(program.is_some(), "Fail to create new program");
+
+            // Create proposal
+
+            set_caller::<Environment>(mock_accounts.4);
+            let result: Result<(), Error> = dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                0,
+                1000 * 1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+
+            assert!(result.is_ok(), "Fail to create proposal");
+
+            // Fiat proposals need a "CRYPTO/FIAT" pair key.
+            for key in ["AZERO", "AZERO/", "/EUR", "AZERO/EUR/USD"] {
+                let result: Result<(), Error> = dao.create_proposal(
+                    0,
+                    "Title".to_string(),
+                    "Description".to_string(),
+                    0,
+                    1000 * 1000,
+                    true,
+                    1_050,
+                    key.to_string(),
+                    0,
+                    mock_param.0,
+                    mock_param.1,
+                    true
+                );
+                assert_eq!(result, Err(Error::InvalidPairKey));
+            }
+            assert_eq!(dao.get_num_proposals(), 1);
+        }
+
+        #[ink::test]
+        fn test_voting_and_execute_proposal() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            set_caller::<Environment>(mock_accounts.0);
+            // Create program
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+
+            let program = dao.get_program(0);
+            assert!(program.is_some(), "Fail to create new program");
+
+            // Create proposal
+
+            set_caller::<Environment>(mock_accounts.4);
+            let result: Result<(), Error> = dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                0,
+                1000 * 1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+
+            assert!(result.is_ok(), "Fail to create proposal");
+
+
+            // Step 0: member voting
+            set_caller::<Environment>(mock_accounts.1);
+            let _ = dao.voting(0, 0, 1);
+            let proposal_votings: Vec<ProposalVoting> = dao.get_steps_voting_status(0);
+            assert_eq!(proposal_votings[0].agree, 1);
+
+            // Step 1: member voting
+            set_caller::<Environment>(mock_accounts.2);
+            let _ = dao.voting(0, 1, 1);
+            let proposal_votings: Vec<ProposalVoting> = dao.get_steps_voting_status(0);
+            assert_eq!(proposal_votings[1].agree, 1);
+
+            let previous_balance = dao.get_contract_balance();
+
+            // execute proposal
+            let _ = dao.execute_proposal(0);
+
+            // Check balance after execution
+            let after_balance = dao.get_contract_balance();
+            let proposal = dao.get_proposal(0);
+            assert!(proposal.is_some());
+            assert!(proposal.unwrap().executed);
+            assert_eq!(previous_balance - after_balance, 200);
+            assert_eq!(dao.get_proposal_status(0), Some(ProposalStatus::Executed));
+            assert_eq!(dao.execute_proposal(0), Err(Error::ProposalHasExecuted));
+        } 
+
+        #[ink::test]
+        fn test_fund_contribution() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+            let native_token = AccountId::from([0x00; 32]);
+
+            set_caller::<Environment>(mock_accounts.3);
+            set_value_transferred::<Environment>(100);
+            assert_eq!(dao.fund(), Ok(()));
+            set_value_transferred::<Environment>(50);
+            assert_eq!(dao.fund(), Ok(()));
+
+            assert_eq!(dao.get_contribution(mock_accounts.3, native_token), 150);
+            assert_eq!(dao.get_contributions(mock_accounts.3), vec![(native_token, 150)]);
+            assert_eq!(dao.get_token_fund(native_token), 150);
+
+            // Totals which would overflow are rejected.
+            set_value_transferred::<Environment>(u128::MAX - 149);
+            assert_eq!(dao.fund(), Err(Error::ArithmeticOverflow));
+            assert_eq!(dao.get_contribution(mock_accounts.3, native_token), 150);
+
+            let emitted_events: Vec<EmittedEvent> = recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 2);
+            assert_topics(&emitted_events[1], &[
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"",
+                    value: b"Dao::Funded",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Dao::Funded::contributor",
+                    value: &mock_accounts.3,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Dao::Funded::token",
+                    value: &native_token,
+                }),
+            ]);
+        }
+
+        #[ink::test]
+        fn test_proposal_events() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+
+            set_caller::<Environment>(mock_accounts.4);
+            let _ = dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                0,
+                1000 * 1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+
+            set_caller::<Environment>(mock_accounts.1);
+            let _ = dao.voting(0, 0, 1);
+            set_caller::<Environment>(mock_accounts.2);
+            let _ = dao.voting(0, 1, 1);
+            let _ = dao.execute_proposal(0);
+
+            let emitted_events: Vec<EmittedEvent> = recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 5);
+            assert_proposal_created_event(&emitted_events[1], 0, 0, mock_accounts.4);
+            assert_voted_event(&emitted_events[2], 0, 0, mock_accounts.1, 1, 1);
+            assert_voted_event(&emitted_events[3], 0, 1, mock_accounts.2, 1, 1);
+            assert_proposal_executed_event(&emitted_events[4], 0, mock_param.1, 200);
+        }
+
+        #[ink::test]
+        fn test_member_events() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+            let new_member = AccountId::from([0x08; 32]);
+            set_caller::<Environment>(mock_accounts.0);
+
+            let _ = dao.add_normal_member(new_member);
+            let _ = dao.remove_normal_member(new_member);
+            // Removing a missing member does not emit an event.
+            let _ = dao.remove_normal_member(new_member);
+            let _ = dao.add_whitelisted_contributor(new_member);
+            let _ = dao.remove_whitelisted_contributor(new_member);
+            let _ = dao.add_step_members(0, new_member);
+            let _ = dao.remove_step_members(0, new_member);
+
+            let emitted_events: Vec<EmittedEvent> = recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 6);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[5].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::StepMemberRemoved(StepMemberRemoved { step_index, member }) = decoded_event {
+                assert_eq!(step_index, 0);
+                assert_eq!(member, new_member);
+            } else {
+                panic!("encountered unexpected event kind: expected a StepMemberRemoved event")
+            }
+        }
+
+        #[ink::test]
+        fn test_pause_and_unpause() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+
+            // Only the owner or admin
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.pause(), Err(Error::MissingRole));
+
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+            assert_eq!(dao.pause(), Ok(()));
+            assert!(!dao.get_info().16);
+
+            set_caller::<Environment>(mock_accounts.3);
+            set_value_transferred::<Environment>(100);
+            assert_eq!(dao.fund(), Err(Error::DaoPaused));
+            assert_eq!(dao.fund_token(AccountId::from([0x06; 32]), 100), Err(Error::DaoPaused));
+
+            set_caller::<Environment>(mock_accounts.4);
+            let create_proposal = |dao: &mut Dao| dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                0,
+                1000 * 1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+            assert_eq!(create_proposal(&mut dao), Err(Error::DaoPaused));
+
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.unpause(), Ok(()));
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(create_proposal(&mut dao), Ok(()));
+
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.pause(), Ok(()));
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.voting(0, 0, 1), Err(Error::DaoPaused));
+            assert_eq!(dao.execute_proposal(0), Err(Error::DaoPaused));
+        }
+
+        #[ink::test]
+        fn test_milestone_payouts() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+
+            // Voting starts at 10
+            set_caller::<Environment>(mock_accounts.4);
+            let _ = dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                10,
+                1000 * 1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+
+            // Only the proposer, within the payment amount
+            assert_eq!(dao.add_milestone(0, "M1".to_string(), 120, 100), Ok(()));
+            assert_eq!(dao.add_milestone(0, "M2".to_string(), 81, 200), Err(Error::MilestoneAmountExceeded));
+            assert_eq!(dao.add_milestone(0, "M2".to_string(), 80, 200), Ok(()));
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.add_milestone(0, "M3".to_string(), 0, 300), Err(Error::NotProposer));
+            assert_eq!(dao.get_milestones(0).len(), 2);
+
+            // Deliverables can be submitted only after the proposal passes.
+            set_block_timestamp::<Environment>(10);
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(dao.add_milestone(0, "M3".to_string(), 0, 300), Err(Error::VotingHasStarted));
+            assert_eq!(dao.submit_milestone(0, 0, "Report".to_string()), Err(Error::ProposalHasNotPassed));
+
+            set_caller::<Environment>(mock_accounts.1);
+            let _ = dao.voting(0, 0, 1);
+            set_caller::<Environment>(mock_accounts.2);
+            let _ = dao.voting(0, 1, 1);
+            assert_eq!(dao.execute_proposal(0), Err(Error::ProposalHasMilestones));
+
+            // The beneficiary submits the first milestone.
+            set_caller::<Environment>(mock_param.1);
+            assert_eq!(dao.milestone_voting(0, 0, 0, 1), Err(Error::NotAllowVoting));
+            assert_eq!(dao.submit_milestone(0, 0, "R".repeat(MAX_DESCRIPTION_LENGTH + 1)), Err(Error::DescriptionTooLong));
+            assert_eq!(dao.submit_milestone(0, 0, "Report".to_string()), Ok(()));
+            assert_eq!(dao.submit_milestone(0, 0, "Report".to_string()), Err(Error::MilestoneHasSubmitted));
+            assert_eq!(dao.submit_milestone(0, 2, "Report".to_string()), Err(Error::MilestoneIndexOutOfBound));
+
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.milestone_voting(0, 1, 0, 1), Err(Error::MilestoneNotSubmitted));
+            assert_eq!(dao.milestone_voting(0, 0, 0, 1), Ok(()));
+            assert_eq!(dao.get_member_milestone_voted(mock_accounts.1, 0, 0, 0), 1);
+
+            // Step 1 has not voted yet.
+            let previous_balance = dao.get_contract_balance();
+            assert_eq!(dao.execute_milestone(0, 0), Ok(()));
+            assert_eq!(dao.get_contract_balance(), previous_balance);
+
+            set_caller::<Environment>(mock_accounts.2);
+            assert_eq!(dao.milestone_voting(0, 0, 1, 1), Ok(()));
+            assert_eq!(dao.execute_milestone(0, 0), Ok(()));
+            assert_eq!(previous_balance - dao.get_contract_balance(), 120);
+            assert_eq!(dao.get_milestones(0)[0].state, MilestoneState::Paid);
+            assert_eq!(dao.get_milestones(0)[0].deliverable, "Report".to_string());
+            assert!(!dao.get_proposal(0).unwrap().executed);
+
+            // The last milestone completes the proposal.
+            set_caller::<Environment>(mock_accounts.4);
+            let _ = dao.submit_milestone(0, 1, "Final report".to_string());
+            set_caller::<Environment>(mock_accounts.1);
+            let _ = dao.milestone_voting(0, 1, 0, 1);
+            set_caller::<Environment>(mock_accounts.2);
+            let _ = dao.milestone_voting(0, 1, 1, 1);
+            assert_eq!(dao.execute_milestone(0, 1), Ok(()));
+            assert_eq!(previous_balance - dao.get_contract_balance(), 200);
+            assert!(dao.get_proposal(0).unwrap().executed);
+            assert_eq!(dao.execute_milestone(0, 1), Err(Error::MilestoneNotSubmitted));
+        }
+
+        #[ink::test]
+        fn test_program_budget() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            let balance = dao.get_contract_balance();
+
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.create_program("Title".to_string(), "Description".to_string(), 0, 1000 * 1000, Some((mock_param.0, 0))), Err(Error::InvalidProgramBudget));
+            assert_eq!(dao.create_program("Title".to_string(), "Description".to_string(), 0, 1000 * 1000, Some((mock_param.0, balance + 1))), Err(Error::NotEnoughBalance));
+            assert_eq!(dao.create_program("Title".to_string(), "Description".to_string(), 0, 1000 * 1000, Some((mock_param.0, 300))), Ok(()));
+            assert_eq!(dao.get_program_budget(0), Some(ProgramBudget { token: mock_param.0, amount: 300, committed: 0, paid: 0 }));
+            assert_eq!(dao.get_allocated_budget(mock_param.0), 300);
+
+            // Allocated funds are not available to other budgets.
+            assert_eq!(dao.create_program("Title".to_string(), "Description".to_string(), 0, 1000 * 1000, Some((mock_param.0, balance - 299))), Err(Error::NotEnoughBalance));
+
+            set_caller::<Environment>(mock_accounts.4);
+            let create_proposal = |dao: &mut Dao, token: AccountId, amount: u128| dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                10,
+                1000 * 1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                amount,
+                token,
+                mock_param.1,
+                true
+            );
+            assert_eq!(create_proposal(&mut dao, AccountId::from([0x09; 32]), 200), Err(Error::ProgramTokenMismatch));
+            assert_eq!(create_proposal(&mut dao, mock_param.0, 301), Err(Error::ExceedProgramBudget));
+            assert_eq!(create_proposal(&mut dao, mock_param.0, 200), Ok(()));
+            assert_eq!(dao.get_proposal_commitment(0), 200);
+
+            // The first proposal reserves its payment, the second one exceeds the remaining budget.
+            assert_eq!(create_proposal(&mut dao, mock_param.0, 200), Err(Error::ExceedProgramBudget));
+            assert_eq!(create_proposal(&mut dao, mock_param.0, 100), Ok(()));
+            assert_eq!(dao.get_program_budget(0), Some(ProgramBudget { token: mock_param.0, amount: 300, committed: 300, paid: 0 }));
+
+            // A withdrawn proposal releases its reservation.
+            assert_eq!(dao.withdraw_proposal(1), Ok(()));
+            assert_eq!(dao.get_proposal_commitment(1), 0);
+            assert_eq!(dao.get_program_budget(0), Some(ProgramBudget { token: mock_param.0, amount: 300, committed: 200, paid: 0 }));
+            assert_eq!(create_proposal(&mut dao, mock_param.0, 100), Ok(()));
+
+            // The first proposal passes, the third one is rejected.
+            set_block_timestamp::<Environment>(10);
+            set_caller::<Environment>(mock_accounts.1);
+            let _ = dao.voting(0, 0, 1);
+            let _ = dao.voting(2, 0, 2);
+            set_caller::<Environment>(mock_accounts.2);
+            let _ = dao.voting(0, 1, 1);
+
+            assert_eq!(dao.execute_proposal(0), Ok(()));
+            assert_eq!(balance - dao.get_contract_balance(), 200);
+            assert_eq!(dao.get_proposal_commitment(0), 0);
+            assert_eq!(dao.get_program_budget(0), Some(ProgramBudget { token: mock_param.0, amount: 300, committed: 300, paid: 200 }));
+            assert_eq!(dao.get_allocated_budget(mock_param.0), 100);
+
+            // A rejected proposal releases its reservation once voting has ended.
+            assert_eq!(dao.release_proposal_budget(2), Err(Error::InvalidProposalStatus));
+            set_block_timestamp::<Environment>(1000 * 1000 + 1);
+            assert_eq!(dao.get_proposal_status(2), Some(ProposalStatus::Rejected));
+            assert_eq!(dao.release_proposal_budget(2), Ok(()));
+            assert_eq!(dao.get_proposal_commitment(2), 0);
+            assert_eq!(dao.get_program_budget(0), Some(ProgramBudget { token: mock_param.0, amount: 300, committed: 200, paid: 200 }));
+            assert_eq!(dao.get_allocated_budget(mock_param.0), 100);
+        }
+
+        #[ink::test]
+        fn test_update_proposal() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            let new_recipient = AccountId::from([0x08; 32]);
+
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program("Title".to_string(), "Description".to_string(), 0, 1000, Some((mock_param.0, 300)));
+
+            set_caller::<Environment>(mock_accounts.4);
+            let _ = dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                10,
+                1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+            assert_eq!(dao.get_proposal_revisions(0).len(), 1);
+            let _ = dao.add_milestone(0, "M1".to_string(), 150, 100);
+
+            // Only the proposer, within the milestones and the program budget
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.update_proposal(0, "Fixed".to_string(), "Fixed".to_string(), 0, 200, new_recipient), Err(Error::NotProposer));
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(dao.update_proposal(1, "Fixed".to_string(), "Fixed".to_string(), 0, 200, new_recipient), Err(Error::ProposalIndexOutOfBound));
+            assert_eq!(dao.update_proposal(0, "Fixed".to_string(), "Fixed".to_string(), 0, 149, new_recipient), Err(Error::MilestoneAmountExceeded));
+            assert_eq!(dao.update_proposal(0, "Fixed".to_string(), "Fixed".to_string(), 0, 301, new_recipient), Err(Error::ExceedProgramBudget));
+            assert_eq!(dao.update_proposal(0, "Fixed".to_string(), "Fixed".to_string(), 0, 250, new_recipient), Ok(()));
+
+            let proposal = dao.get_proposal(0).unwrap();
+            assert_eq!((proposal.title, proposal.payment_amount_crypto, proposal.to), ("Fixed".to_string(), 250, new_recipient));
+            let revisions = dao.get_proposal_revisions(0);
+            assert_eq!(revisions.len(), 2);
+            assert_eq!(revisions[1].revision, 1);
+            assert_ne!(revisions[0].content_hash, revisions[1].content_hash);
+
+            // Reverting the content reverts the content hash, there were no votes to reset.
+            set_block_timestamp::<Environment>(5);
+            assert_eq!(dao.update_proposal(0, "Title".to_string(), "Description".to_string(), 0, 200, mock_param.1), Ok(()));
+            assert_eq!(dao.get_proposal_revisions(0)[2].content_hash, dao.get_proposal_revisions(0)[0].content_hash);
+            let emitted_events = recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::ProposalUpdated(ProposalUpdated { proposal_index, revision, votes_reset, .. }) = decoded_event {
+                assert_eq!((proposal_index, revision, votes_reset), (0, 2, false));
+            } else {
+                panic!("encountered unexpected event kind: expected a ProposalUpdated event")
+            }
+
+            // A proposal can not be updated once voting has started.
+            set_block_timestamp::<Environment>(10);
+            assert_eq!(dao.update_proposal(0, "Fixed".to_string(), "Fixed".to_string(), 0, 250, new_recipient), Err(Error::VotingHasStarted));
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.voting(0, 0, 1), Ok(()));
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(dao.update_proposal(0, "Fixed".to_string(), "Fixed".to_string(), 0, 250, new_recipient), Err(Error::VotingHasStarted));
+            assert_eq!(dao.get_proposal(0).unwrap().to, mock_param.1);
+            assert_eq!(dao.get_steps_voting_status(0)[0].agree, 1);
+
+            // A cancelled proposal can not be updated.
+            let _ = dao.create_proposal(0, "Title".to_string(), "Description".to_string(), 30, 1000, false, 0, "AZERO/USD".to_string(), 50, mock_param.0, mock_param.1, true);
+            let _ = dao.withdraw_proposal(1);
+            assert_eq!(dao.update_proposal(1, "Fixed".to_string(), "Fixed".to_string(), 0, 50, new_recipient), Err(Error::ProposalHasCancelled));
+        }
+
+        #[ink::test]
+        fn test_content_cids() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            let cid: Vec<u8> = vec![0x12; 34];
+            let long_title: String = "T".repeat(MAX_TITLE_LENGTH + 1);
+            let long_description: String = "D".repeat(MAX_DESCRIPTION_LENGTH + 1);
+
+            // Inline texts are limited in length.
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.create_program(long_title.clone(), "Description".to_string(), 0, 1000, None), Err(Error::TitleTooLong));
+            assert_eq!(dao.create_program("Title".to_string(), long_description.clone(), 0, 1000, None), Err(Error::DescriptionTooLong));
+            assert_eq!(dao.create_program("Title".to_string(), "Description".to_string(), 0, 1000, None), Ok(()));
+            assert_eq!(dao.update_program(0, "Title".to_string(), long_description.clone()), Err(Error::DescriptionTooLong));
+            assert_eq!(dao.update_dao_info(long_description.clone(), vec![]), Err(Error::DescriptionTooLong));
+            assert_eq!(dao.update_dao_info("Description".to_string(), vec!["@dao".to_string(); MAX_SOCIAL_ACCOUNTS + 1]), Err(Error::TooManySocialAccounts));
+            assert_eq!(dao.update_dao_info("Description".to_string(), vec!["@".repeat(MAX_SOCIAL_ACCOUNT_LENGTH + 1)]), Err(Error::SocialAccountTooLong));
+            assert_eq!(dao.update_dao_info("Short description".to_string(), vec!["@dao".to_string()]), Ok(()));
+            assert_eq!(dao.get_info().3, "Short description".to_string());
+
+            // DAO and program CIDs
+            assert_eq!(dao.set_content_cid(ContentKey::DaoDescription, Some(vec![])), Err(Error::InvalidContentCid));
+            assert_eq!(dao.set_content_cid(ContentKey::DaoDescription, Some(vec![0x12; MAX_CID_LENGTH + 1])), Err(Error::InvalidContentCid));
+            assert_eq!(dao.set_content_cid(ContentKey::DaoDescription, Some(cid.clone())), Ok(()));
+            assert_eq!(dao.set_content_cid(ContentKey::Program(1), Some(cid.clone())), Err(Error::ProgramIndexOutOfBound));
+            assert_eq!(dao.set_content_cid(ContentKey::Program(0), Some(cid.clone())), Ok(()));
+            assert_eq!(dao.get_content_cid(ContentKey::DaoDescription), Some(cid.clone()));
+            assert_eq!(dao.get_content_cid(ContentKey::SocialAccounts), None);
+
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(dao.update_dao_info("Description".to_string(), vec![]), Err(Error::NotAdmin));
+            assert_eq!(dao.set_content_cid(ContentKey::SocialAccounts, Some(cid.clone())), Err(Error::NotAdmin));
+            assert_eq!(dao.set_content_cid(ContentKey::Program(0), None), Err(Error::MissingRole));
+
+            // Proposal CIDs are set by the proposer and recorded as revisions.
+            let create_proposal = |dao: &mut Dao, title: String, description: String| dao.create_proposal(
+                0,
+                title,
+                description,
+                10,
+                1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                100,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+            assert_eq!(create_proposal(&mut dao, long_title.clone(), "Description".to_string()), Err(Error::TitleTooLong));
+            assert_eq!(create_proposal(&mut dao, "Title".to_string(), long_description.clone()), Err(Error::DescriptionTooLong));
+            assert_eq!(create_proposal(&mut dao, "Title".to_string(), "Description".to_string()), Ok(()));
+            assert_eq!(dao.update_proposal(0, long_title, "Description".to_string(), 0, 100, mock_param.1), Err(Error::TitleTooLong));
+
+            assert_eq!(dao.set_content_cid(ContentKey::Proposal(0), Some(cid.clone())), Ok(()));
+            assert_eq!(dao.get_content_cid(ContentKey::Proposal(0)), Some(cid.clone()));
+            let revisions = dao.get_proposal_revisions(0);
+            assert_eq!(revisions.len(), 2);
+            assert_ne!(revisions[0].content_hash, revisions[1].content_hash);
+
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.set_content_cid(ContentKey::Proposal(0), None), Err(Error::NotProposer));
+            set_caller::<Environment>(mock_accounts.4);
+            set_block_timestamp::<Environment>(10);
+            assert_eq!(dao.set_content_cid(ContentKey::Proposal(0), None), Err(Error::VotingHasStarted));
+        }
+
+        #[ink::test]
+        fn test_program_lifecycle() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program("Title".to_string(), "Description".to_string(), 0, 1000, Some((mock_param.0, 300)));
+            let _ = dao.create_program("Second".to_string(), "Description".to_string(), 0, 1000, None);
+
+            // Only program managers can change programs.
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(dao.update_program(0, "Fixed".to_string(), "Fixed description".to_string()), Err(Error::MissingRole));
+            assert_eq!(dao.close_program(0), Err(Error::MissingRole));
+
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.update_program(2, "Fixed".to_string(), "Fixed description".to_string()), Err(Error::ProgramIndexOutOfBound));
+            assert_eq!(dao.update_program(0, "Fixed".to_string(), "Fixed description".to_string()), Ok(()));
+            assert_eq!(dao.set_program_dates(0, 100, 99), Err(Error::InvalidProgramDates));
+            assert_eq!(dao.set_program_dates(0, 0, 2000), Ok(()));
+            let program = dao.get_program(0).unwrap();
+            assert_eq!((program.program_index, program.title, program.end_date), (0, "Fixed".to_string(), 2000));
+
+            let create_proposal = |dao: &mut Dao, program_index: u32| dao.create_proposal(
+                program_index,
+                "Title".to_string(),
+                "Description".to_string(),
+                10,
+                1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                100,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(create_proposal(&mut dao, 0), Ok(()));
+
+            // Only closed programs can be archived.
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.archive_program(0), Err(Error::ProgramNotClosed));
+            assert_eq!(dao.close_program(0), Ok(()));
+            assert_eq!(dao.close_program(0), Err(Error::ProgramClosed));
+            assert_eq!(dao.get_program_state(0), ProgramState::Closed);
+            assert_eq!(dao.get_program_state(1), ProgramState::Open);
+
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(create_proposal(&mut dao, 0), Err(Error::ProgramClosed));
+            assert_eq!(create_proposal(&mut dao, 1), Ok(()));
+            assert_eq!(dao.get_proposal(1).unwrap().program_index, 1);
+
+            // Archiving returns the unreserved budget to the treasury.
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.archive_program(0), Ok(()));
+            assert_eq!(dao.get_program_state(0), ProgramState::Archived);
+            assert_eq!(dao.get_program_budget(0).unwrap().amount, 100);
+            assert_eq!(dao.get_allocated_budget(mock_param.0), 100);
+
+            // The reservation of a cancelled proposal follows.
+            assert_eq!(dao.cancel_proposal(0), Ok(()));
+            assert_eq!(dao.get_program_budget(0).unwrap().amount, 0);
+            assert_eq!(dao.get_allocated_budget(mock_param.0), 0);
+            assert_eq!(dao.update_program(0, "Title".to_string(), "Description".to_string()), Err(Error::ProgramArchived));
+            assert_eq!(dao.set_program_dates(0, 0, 3000), Err(Error::ProgramArchived));
+            assert_eq!(dao.archive_program(0), Err(Error::ProgramArchived));
+            assert_eq!(dao.get_programs().len(), 2);
+        }
+
+        #[ink::test]
+        fn test_add_milestone_fail() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program("Program Title".to_string(), "Program Description".to_string(), 0, 1000 * 1000, None);
+
+            set_caller::<Environment>(mock_accounts.4);
+            let _ = dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                10,
+                1000 * 1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+
+            assert_eq!(dao.add_milestone(0, "T".repeat(MAX_TITLE_LENGTH + 1), 100, 100), Err(Error::TitleTooLong));
+            assert_eq!(dao.add_milestone(0, "M1".to_string(), 100, 100), Ok(()));
+            // A wrapping total must not get past the payment amount check.
+            assert_eq!(dao.add_milestone(0, "M2".to_string(), u128::MAX - 99, 200), Err(Error::ArithmeticOverflow));
+
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.pause();
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(dao.add_milestone(0, "M2".to_string(), 100, 200), Err(Error::DaoPaused));
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.unpause();
+
+            set_caller::<Environment>(mock_accounts.4);
+            let _ = dao.withdraw_proposal(0);
+            assert_eq!(dao.add_milestone(0, "M2".to_string(), 100, 200), Err(Error::ProposalHasCancelled));
+            assert_eq!(dao.get_milestones(0).len(), 1);
+        }
+
+        #[ink::test]
+        fn test_large_voting_weights() {
+            let mock_accounts = get_mock_accounts();
+            let mock_param = get_mock_proposal_params();
+            let senior_member = AccountId::from([0x08; 32]);
+            let mut dao = Dao::new(
+                mock_accounts.5,
+                mock_accounts.0,
+                "Name".to_string(),
+                "Description".to_string(),
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                vec![],
+                vec![
+                    Step {
+                        step_index: 0,
+                        title: "Step 1".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::FixedWeight,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                ],
+                vec![vec![mock_accounts.1, senior_member]],
+                vec![mock_accounts.3],
+                50,
+                100,
+                vec![mock_accounts.4],
+                false,
+                false,
+            ).unwrap();
+
+            // Weights close to u128::MAX do not overflow the quorum and threshold math.
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.set_member_weight(0, senior_member, u128::MAX / 2), Ok(()));
+            assert_eq!(dao.set_member_weight(0, mock_accounts.1, u128::MAX / 2), Ok(()));
+            assert_eq!(dao.set_member_weight(0, senior_member, u128::MAX), Err(Error::ArithmeticOverflow));
+            let _ = dao.create_program("Program Title".to_string(), "Program Description".to_string(), 0, 1000 * 1000, None);
+
+            set_caller::<Environment>(mock_accounts.4);
+            let _ = dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                0,
+                1000 * 1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+
+            // Exactly half of the total weight reaches the 50% quorum.
+            set_caller::<Environment>(senior_member);
+            assert_eq!(dao.voting(0, 0, 1), Ok(()));
+            assert_eq!(dao.get_proposal_status(0), Some(ProposalStatus::Passed));
+            assert_eq!(dao.execute_proposal(0), Ok(()));
+            assert!(dao.get_proposal(0).unwrap().executed);
+        }
+
+        #[ink::test]
+        fn test_fixed_weight_voting() {
+            let mock_accounts = get_mock_accounts();
+            let mock_param = get_mock_proposal_params();
+            let senior_member = AccountId::from([0x08; 32]);
+            let mut dao = Dao::new(
+                mock_accounts.5,
+                mock_accounts.0,
+                "Name".to_string(),
+                "Description".to_string(),
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                vec![],
+                vec![
+                    Step {
+                        step_index: 0,
+                        title: "Step 1".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::FixedWeight,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                ],
+                vec![vec![mock_accounts.1, senior_member]],
+                vec![mock_accounts.3],
+                50,
+                100,
+                vec![mock_accounts.4],
+                false,
+                false,
+            ).unwrap();
+
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.set_member_weight(0, senior_member, 3), Err(Error::MissingRole));
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.set_member_weight(1, senior_member, 3), Err(Error::StepIndexOutOfBound));
+            assert_eq!(dao.set_member_weight(0, senior_member, 3), Ok(()));
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+
+            set_caller::<Environment>(mock_accounts.4);
+            let _ = dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                0,
+                1000 * 1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+            assert_eq!(dao.get_voting_weight(0, 0, mock_accounts.1), 1);
+            assert_eq!(dao.get_voting_weight(0, 0, senior_member), 3);
+
+            // Half of the members but only 1/4 of the weight: quorum is not reached.
+            set_caller::<Environment>(mock_accounts.1);
+            let _ = dao.voting(0, 0, 1);
+            let _ = dao.execute_proposal(0);
+            assert!(!dao.get_proposal(0).unwrap().executed);
+
+            set_caller::<Environment>(senior_member);
+            let _ = dao.voting(0, 0, 1);
+            assert_eq!(dao.get_steps_voting_status(0)[0].agree, 4);
+            let _ = dao.execute_proposal(0);
+            assert!(dao.get_proposal(0).unwrap().executed);
+        }
+
+        #[ink::test]
+        fn test_fund_fail() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+            let mock_param = get_mock_proposal_params();
+            let token = AccountId::from([0x06; 32]);
+
+            // Only whitelisted contributors
+            set_caller::<Environment>(mock_accounts.4);
+            set_value_transferred::<Environment>(100);
+            assert_eq!(dao.fund(), Err(Error::NotWhitelistedContributor));
+            assert_eq!(dao.fund_token(token, 100), Err(Error::NotWhitelistedContributor));
+
+            set_caller::<Environment>(mock_accounts.3);
+            assert_eq!(dao.fund_token(token, 0), Err(Error::ZeroFundAmount));
+            assert_eq!(dao.fund_token(mock_param.0, 100), Err(Error::InvalidToken));
+            assert!(dao.get_contributions(mock_accounts.3).is_empty());
+        }
+
+        #[ink::test]
+        fn test_proposal_status() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+
+            set_caller::<Environment>(mock_accounts.4);
+            for _ in 0..4 {
+                let _ = dao.create_proposal(
+                    0,
+                    "Title".to_string(),
+                    "Description".to_string(),
+                    100,
+                    1000,
+                    false,
+                    0,
+                    "AZERO/USD".to_string(),
+                    200,
+                    mock_param.0,
+                    mock_param.1,
+                    true
+                );
+            }
+            assert_eq!(dao.get_proposal_status(0), Some(ProposalStatus::Pending));
+            assert_eq!(dao.get_proposal_status(4), None);
+
+            // Only the proposer can withdraw a proposal.
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.withdraw_proposal(0), Err(Error::NotProposer));
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(dao.withdraw_proposal(0), Ok(()));
+            assert_eq!(dao.get_proposal_status(0), Some(ProposalStatus::Cancelled));
+            assert_eq!(dao.withdraw_proposal(0), Err(Error::InvalidProposalStatus));
+
+            // Only the admin can cancel a proposal.
+            assert_eq!(dao.cancel_proposal(1), Err(Error::MissingRole));
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.cancel_proposal(1), Ok(()));
+            assert_eq!(dao.cancel_proposal(1), Err(Error::InvalidProposalStatus));
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.set_execution_period(500), Err(Error::MissingRole));
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.set_execution_period(500), Ok(()));
+            assert_eq!(dao.get_execution_period(), 500);
+
+            set_block_timestamp::<Environment>(100);
+            assert_eq!(dao.get_proposal_status(2), Some(ProposalStatus::Active));
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.voting(1, 0, 1), Err(Error::ProposalHasCancelled));
+            assert_eq!(dao.voting(2, 0, 2), Ok(()));
+            assert_eq!(dao.voting(3, 0, 1), Ok(()));
+            set_caller::<Environment>(mock_accounts.2);
+            assert_eq!(dao.voting(3, 1, 1), Ok(()));
+            assert_eq!(dao.get_proposal_status(3), Some(ProposalStatus::Passed));
+
+            // A passed proposal can not be withdrawn.
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(dao.withdraw_proposal(3), Err(Error::InvalidProposalStatus));
+
+            // Voting ends without qualifying all workflow steps.
+            set_block_timestamp::<Environment>(1001);
+            assert_eq!(dao.get_proposal_status(2), Some(ProposalStatus::Rejected));
+            assert_eq!(dao.execute_proposal(2), Ok(()));
+            assert_eq!(dao.execute_proposal(2), Err(Error::ProposalHasRejected));
+            assert_eq!(dao.execute_proposal(1), Err(Error::ProposalHasCancelled));
+
+            // Not executed within the execution period.
+            set_block_timestamp::<Environment>(1501);
+            assert_eq!(dao.get_proposal_status(3), Some(ProposalStatus::Expired));
+            assert_eq!(dao.execute_proposal(3), Err(Error::ProposalHasExpired));
+        }
+
+        #[ink::test]
+        fn test_sequential_workflow() {
+            let mock_accounts = get_mock_accounts();
+            let mock_param = get_mock_proposal_params();
+            let third_member = AccountId::from([0x08; 32]);
+            let mut dao = Dao::new(
+                mock_accounts.5,
+                mock_accounts.0,
+                "Name".to_string(),
+                "Description".to_string(),
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                vec![],
+                vec![
+                    Step {
+                        step_index: 0,
+                        title: "Technical review".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                    Step {
+                        step_index: 1,
+                        title: "Finance".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 50,
+                    },
+                ],
+                vec![vec![mock_accounts.1, third_member], vec![mock_accounts.2]],
+                vec![mock_accounts.3],
+                100,
+                100,
+                vec![mock_accounts.4],
+                false,
+                false,
+            ).unwrap();
+
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.set_workflow_mode(WorkflowMode::Sequential), Err(Error::MissingRole));
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.set_workflow_mode(WorkflowMode::Sequential), Ok(()));
+            assert_eq!(dao.get_workflow_mode(), WorkflowMode::Sequential);
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+
+            set_caller::<Environment>(mock_accounts.4);
+            for _ in 0..2 {
+                let _ = dao.create_proposal(
+                    0,
+                    "Title".to_string(),
+                    "Description".to_string(),
+                    0,
+                    1000,
+                    false,
+                    0,
+                    "AZERO/USD".to_string(),
+                    200,
+                    mock_param.0,
+                    mock_param.1,
+                    true
+                );
+            }
+
+            // Step 1 can not vote before step 0 passes.
+            set_caller::<Environment>(mock_accounts.2);
+            assert_eq!(dao.voting(0, 1, 1), Err(Error::StepNotActive));
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.voting(0, 0, 1), Ok(()));
+            assert_eq!(dao.voting(1, 0, 1), Ok(()));
+            assert_eq!(dao.get_step_passed_at(0, 0), None);
+            set_caller::<Environment>(mock_accounts.2);
+            assert_eq!(dao.voting(0, 1, 1), Err(Error::StepNotActive));
+
+            set_block_timestamp::<Environment>(10);
+            set_caller::<Environment>(third_member);
+            assert_eq!(dao.voting(0, 0, 1), Ok(()));
+            assert_eq!(dao.voting(1, 0, 1), Ok(()));
+            assert_eq!(dao.get_step_passed_at(0, 0), Some(10));
+
+            // Step 1 votes within its voting window.
+            set_block_timestamp::<Environment>(60);
+            set_caller::<Environment>(mock_accounts.2);
+            assert_eq!(dao.voting(0, 1, 1), Ok(()));
+            assert_eq!(dao.execute_proposal(0), Ok(()));
+            assert_eq!(dao.get_proposal_status(0), Some(ProposalStatus::Executed));
+
+            set_block_timestamp::<Environment>(61);
+            assert_eq!(dao.voting(1, 1, 1), Err(Error::StepNotActive));
+
+            // Step 0 passes without a vote once the member who has not voted is removed.
+            set_caller::<Environment>(mock_accounts.4);
+            let _ = dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                0,
+                1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.voting(2, 0, 1), Ok(()));
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.remove_step_members(0, third_member), Ok(()));
+
+            set_block_timestamp::<Environment>(100);
+            set_caller::<Environment>(mock_accounts.2);
+            assert_eq!(dao.voting(2, 1, 1), Ok(()));
+            assert_eq!(dao.get_step_passed_at(2, 0), Some(100));
+            assert_eq!(dao.get_proposal_status(2), Some(ProposalStatus::Passed));
+        }
+
+        #[ink::test]
+        fn test_paginated_getters() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            let new_member = AccountId::from([0x08; 32]);
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.add_normal_member(new_member);
+            for _ in 0..3 {
+                let _ = dao.create_program(
+                    "Program Title".to_string(),
+                    "Program Description".to_string(),
+                    0,
+                    1000 * 1000,
+                    None
+                );
+            }
+
+            // Proposals 0..5 in program 0 by the first member, 5..8 in program 1 by the new member.
+            for i in 0..8 {
+                let (program_index, proposer) = if i < 5 { (0, mock_accounts.4) } else { (1, new_member) };
+                set_caller::<Environment>(proposer);
+                let _ = dao.create_proposal(
+                    program_index,
+                    "Title".to_string(),
+                    "Description".to_string(),
+                    0,
+                    1000 * 1000,
+                    false,
+                    0,
+                    "AZERO/USD".to_string(),
+                    200,
+                    mock_param.0,
+                    mock_param.1,
+                    true
+                );
+            }
+            set_caller::<Environment>(new_member);
+            let _ = dao.withdraw_proposal(7);
+
+            assert_eq!(dao.get_num_programs(), 3);
+            assert_eq!(dao.get_num_proposals(), 8);
+            let programs = dao.get_programs_paginated(1, 10);
+            assert_eq!(programs.len(), 2);
+            assert_eq!(programs[0].program_index, 1);
+            assert!(dao.get_programs_paginated(3, 10).is_empty());
+
+            let indexes = |proposals: Vec<Proposal>| -> Vec<u32> {
+                proposals.iter().map(|p| p.proposal_index).collect()
+            };
+            assert_eq!(indexes(dao.get_proposals(ProposalFilter::default(), 2, 3)), vec![2, 3, 4]);
+            let by_program = ProposalFilter { program_index: Some(1), ..Default::default() };
+            assert_eq!(indexes(dao.get_proposals(by_program, 1, 10)), vec![6, 7]);
+            let by_proposer = ProposalFilter { proposer: Some(mock_accounts.4), ..Default::default() };
+            assert_eq!(indexes(dao.get_proposals(by_proposer, 3, 10)), vec![3, 4]);
+            let by_status = ProposalFilter {
+                proposer: Some(new_member),
+                status: Some(ProposalStatus::Active),
+                ..Default::default()
+            };
+            assert_eq!(indexes(dao.get_proposals(by_status, 0, 10)), vec![5, 6]);
+            let by_executed = ProposalFilter { executed: Some(true), ..Default::default() };
+            assert!(dao.get_proposals(by_executed, 0, 10).is_empty());
+            let unknown_program = ProposalFilter { program_index: Some(3), ..Default::default() };
+            assert!(dao.get_proposals(unknown_program, 0, 10).is_empty());
+
+            // The page size is capped.
+            set_caller::<Environment>(mock_accounts.0);
+            for i in 0..MAX_PAGE_LIMIT {
+                let _ = dao.add_whitelisted_contributor(AccountId::from([i as u8 + 0x10; 32]));
+            }
+            assert_eq!(dao.get_whitelisted_contributors_paginated(0, MAX_PAGE_LIMIT + 10).len() as u32, MAX_PAGE_LIMIT);
+            assert_eq!(dao.get_normal_members_paginated(1, 10), vec![new_member]);
+            assert_eq!(dao.get_step_members_paginated(1, 0, 10), vec![mock_accounts.2]);
+            assert!(dao.get_step_members_paginated(2, 0, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn test_storage_cost_is_flat() {
+            let mut dao = init_dao();
+            let mock_param = get_mock_proposal_params();
+            let mock_accounts = get_mock_accounts();
+            let contract = ink::env::test::callee::<Environment>();
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+
+            let create_proposals = |dao: &mut Dao, num_proposals: u32| {
+                set_caller::<Environment>(mock_accounts.4);
+                for _ in 0..num_proposals {
+                    let _ = dao.create_proposal(
+                        0,
+                        "Title".to_string(),
+                        "Description".to_string(),
+                        0,
+                        1000 * 1000,
+                        false,
+                        0,
+                        "AZERO/USD".to_string(),
+                        200,
+                        mock_param.0,
+                        mock_param.1,
+                        true
+                    );
+                }
+            };
+            // Size of the root storage cell, decoded and encoded by every message.
+            let root_size = |dao: &Dao| -> usize {
+                let mut buffer: Vec<u8> = Vec::new();
+                ink::storage::traits::Storable::encode(dao, &mut buffer);
+                buffer.len()
+            };
+            // Storage reads and writes of a vote.
+            let vote_cost = |dao: &mut Dao, proposal_index: u32| -> (usize, usize) {
+                set_caller::<Environment>(mock_accounts.1);
+                let (reads, writes) = get_contract_storage_rw::<Environment>(&contract);
+                assert_eq!(dao.voting(proposal_index, 0, 1), Ok(()));
+                let (new_reads, new_writes) = get_contract_storage_rw::<Environment>(&contract);
+                (new_reads - reads, new_writes - writes)
+            };
+
+            create_proposals(&mut dao, 1);
+            let first_root_size = root_size(&dao);
+            let first_vote_cost = vote_cost(&mut dao, 0);
+            assert!(first_vote_cost.0 > 0 && first_vote_cost.1 > 0);
+
+            create_proposals(&mut dao, 200);
+            assert_eq!(dao.get_num_proposals(), 201);
+            assert_eq!(root_size(&dao), first_root_size);
+            assert_eq!(vote_cost(&mut dao, 200), first_vote_cost);
+
+            // Programs are not stored in the root cell either.
+            set_caller::<Environment>(mock_accounts.0);
+            for _ in 0..50 {
+                let _ = dao.create_program("Program Title".to_string(), "Program Description".to_string(), 0, 1000 * 1000, None);
+            }
+            assert_eq!(dao.get_num_programs(), 51);
+            assert_eq!(root_size(&dao), first_root_size);
+        }
+
+        #[ink::test]
+        fn test_member_sets() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+            let new_members = [AccountId::from([0x08; 32]), AccountId::from([0x09; 32])];
+            set_caller::<Environment>(mock_accounts.0);
+            for member in new_members {
+                assert_eq!(dao.add_normal_member(member), Ok(()));
+            }
+            assert_eq!(dao.add_normal_member(new_members[0]), Err(Error::NormalMemberExisted));
+
+            // The last member takes the index of a removed member.
+            assert_eq!(dao.remove_normal_member(mock_accounts.4), Ok(()));
+            assert_eq!(dao.get_normal_members(), vec![new_members[1], new_members[0]]);
+            assert_eq!(dao.get_info().13, 2);
+            assert_eq!(dao.add_normal_member(mock_accounts.4), Ok(()));
+            assert_eq!(dao.get_normal_members_paginated(2, 10), vec![mock_accounts.4]);
+
+            assert_eq!(dao.add_step_members(2, new_members[0]), Err(Error::StepIndexOutOfBound));
+            assert_eq!(dao.remove_step_members(2, mock_accounts.1), Err(Error::StepIndexOutOfBound));
+            assert_eq!(dao.add_step_members(0, new_members[0]), Ok(()));
+            assert_eq!(dao.add_step_members(0, new_members[0]), Err(Error::StepMemberExisted));
+            assert_eq!(dao.remove_step_members(0, mock_accounts.1), Ok(()));
+            assert_eq!(dao.get_step_members(), vec![vec![new_members[0]], vec![mock_accounts.2]]);
+        }
+
+        #[ink::test]
+        fn test_ownership_and_admin_transfer() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+            let owner: AccountId = dao.get_info().0;
+            let new_owner = AccountId::from([0x08; 32]);
+            let new_admin = AccountId::from([0x09; 32]);
+
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.transfer_ownership(new_owner), Err(Error::NotOwner));
+            assert_eq!(dao.transfer_admin(new_admin), Err(Error::NotAdminOrOwner));
+
+            // The new owner has to accept the ownership.
+            set_caller::<Environment>(owner);
+            assert_eq!(dao.transfer_ownership(new_owner), Ok(()));
+            assert_eq!(dao.get_pending_transfers(), (Some(new_owner), None));
+            assert_eq!(dao.get_info().0, owner);
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.accept_ownership(), Err(Error::NotPendingOwner));
+            set_caller::<Environment>(new_owner);
+            assert_eq!(dao.accept_ownership(), Ok(()));
+            assert_eq!(dao.get_info().0, new_owner);
+            assert_eq!(dao.accept_ownership(), Err(Error::NotPendingOwner));
+
+            // The owner can replace the admin.
+            assert_eq!(dao.transfer_admin(new_admin), Ok(()));
+            assert_eq!(dao.get_pending_transfers(), (None, Some(new_admin)));
+            assert_eq!(dao.accept_admin(), Err(Error::NotPendingAdmin));
+            set_caller::<Environment>(new_admin);
+            assert_eq!(dao.accept_admin(), Ok(()));
+            assert_eq!(dao.get_info().1, new_admin);
+            assert_eq!(dao.get_pending_transfers(), (None, None));
+
+            // The previous admin lost its privileges.
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.set_execution_period(1), Err(Error::MissingRole));
+
+            let emitted_events = recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 4);
+            let decoded_events: Vec<Event> = emitted_events
+                .iter()
+                .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert!(matches!(decoded_events[0], Event::OwnershipTransferStarted(OwnershipTransferStarted { previous_owner, new_owner: to }) if previous_owner == owner && to == new_owner));
+            assert!(matches!(decoded_events[1], Event::OwnershipTransferred(OwnershipTransferred { previous_owner, new_owner: to }) if previous_owner == owner && to == new_owner));
+            assert!(matches!(decoded_events[2], Event::AdminTransferStarted(AdminTransferStarted { previous_admin, new_admin: to }) if previous_admin == mock_accounts.0 && to == new_admin));
+            assert!(matches!(decoded_events[3], Event::AdminTransferred(AdminTransferred { previous_admin, new_admin: to }) if previous_admin == mock_accounts.0 && to == new_admin));
+        }
+
+        #[ink::test]
+        fn test_roles() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+            let program_manager = AccountId::from([0x08; 32]);
+            let membership_manager = AccountId::from([0x09; 32]);
+            let pauser = AccountId::from([0x0a; 32]);
+
+            // The admin holds every role.
+            assert!(dao.has_role(Role::TreasuryManager, mock_accounts.0));
+            assert!(!dao.has_role(Role::ProgramManager, program_manager));
+
+            set_caller::<Environment>(program_manager);
+            assert_eq!(dao.grant_role(Role::ProgramManager, program_manager), Err(Error::NotAdmin));
+            assert_eq!(dao.revoke_role(Role::ProgramManager, program_manager), Err(Error::NotAdmin));
+
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.grant_role(Role::ProgramManager, program_manager), Ok(()));
+            assert_eq!(dao.grant_role(Role::ProgramManager, program_manager), Err(Error::RoleExisted));
+            assert_eq!(dao.grant_role(Role::MembershipManager, membership_manager), Ok(()));
+            assert_eq!(dao.grant_role(Role::Pauser, pauser), Ok(()));
+            assert!(dao.has_role(Role::ProgramManager, program_manager));
+            assert!(!dao.has_role(Role::MembershipManager, program_manager));
+            assert_eq!(dao.get_role_members(Role::ProgramManager, 0, 10), vec![program_manager]);
+
+            // Each role only grants its own messages.
+            set_caller::<Environment>(program_manager);
+            assert_eq!(dao.create_program("Title".to_string(), "Description".to_string(), 0, 1000, None), Ok(()));
+            assert_eq!(dao.add_normal_member(pauser), Err(Error::MissingRole));
+            assert_eq!(dao.pause(), Err(Error::MissingRole));
+
+            set_caller::<Environment>(membership_manager);
+            assert_eq!(dao.add_normal_member(pauser), Ok(()));
+            assert_eq!(dao.add_step_members(0, pauser), Ok(()));
+            assert_eq!(dao.create_program("Title".to_string(), "Description".to_string(), 0, 1000, None), Err(Error::MissingRole));
+            assert_eq!(dao.set_price_guards(1, 1), Err(Error::MissingRole));
+
+            set_caller::<Environment>(pauser);
+            assert_eq!(dao.pause(), Ok(()));
+            assert_eq!(dao.unpause(), Ok(()));
+
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.revoke_role(Role::Pauser, pauser), Ok(()));
+            assert!(dao.get_role_members(Role::Pauser, 0, 10).is_empty());
+            set_caller::<Environment>(pauser);
+            assert_eq!(dao.pause(), Err(Error::MissingRole));
+
+            let emitted_events = recorded_events().collect::<Vec<_>>();
+            let decoded_events: Vec<Event> = emitted_events
+                .iter()
+                .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            assert!(matches!(decoded_events[0], Event::RoleGranted(RoleGranted { role: Role::ProgramManager, account }) if account == program_manager));
+            assert!(matches!(decoded_events.last(), Some(Event::RoleRevoked(RoleRevoked { role: Role::Pauser, account })) if *account == pauser));
+        }
+
+        #[ink::test]
+        fn test_upgrade_and_migrate() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+            let owner: AccountId = dao.get_info().0;
+            assert_eq!(dao.get_storage_version(), STORAGE_VERSION);
+
+            // Only the owner, the admin can not upgrade.
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.upgrade(Hash::from([0x01; 32])), Err(Error::NotOwner));
+            assert_eq!(dao.migrate(), Err(Error::NotOwner));
+
+            // The storage is up to date.
+            set_caller::<Environment>(owner);
+            assert_eq!(dao.migrate(), Ok(()));
+            assert_eq!(dao.get_storage_version(), STORAGE_VERSION);
+
+            // Version 1 kept programs in the root storage.
+            let program = Program {
+                program_index: 0,
+                title: "Program Title".to_string(),
+                description: "Program Description".to_string(),
+                start_date: 0,
+                end_date: 1000,
+            };
+            dao.legacy_programs = vec![program.clone(), Program { program_index: 1, ..program.clone() }];
+            dao.storage_version = 1;
+            assert_eq!(dao.get_num_programs(), 0);
+
+            // Version 1 kept u32 fiat amounts in whole units.
+            let mock_param = get_mock_proposal_params();
+            let legacy_proposal = ProposalV1 {
+                program_index: 0,
+                proposal_index: 0,
+                proposer: mock_accounts.4,
+                title: "Title".to_string(),
+                description: "Description".to_string(),
+                start_date: 10,
+                end_date: 1000,
+                use_fiat: true,
+                payment_amount_fiat: 150,
+                cryto_fiat_key: "AZERO/USD".to_string(),
+                payment_amount_crypto: 0,
+                token: mock_param.0,
+                to: mock_param.1,
+                allow_early_executed: true,
+                executed: false,
+                status: ProposalStatus::Pending,
+            };
+            ink::env::set_contract_storage(&(&dao.proposals.key(), 0u32), &legacy_proposal);
+            ink::env::set_contract_storage(&(&dao.proposals.key(), 1u32), &ProposalV1 { proposal_index: 1, use_fiat: false, payment_amount_fiat: 0, payment_amount_crypto: 200, ..legacy_proposal.clone() });
+            dao.num_proposals = 2;
+            dao.proposal_milestones.insert(0, &vec![Milestone { amount: 100, ..Default::default() }, Milestone { milestone_index: 1, amount: 50, ..Default::default() }]);
+            dao.proposal_milestones.insert(1, &vec![Milestone { amount: 200, ..Default::default() }]);
+
+            assert_eq!(dao.migrate(), Ok(()));
+            assert_eq!(dao.get_storage_version(), 2);
+            assert_eq!(dao.get_num_programs(), 2);
+            assert_eq!(dao.get_program(1).unwrap().program_index, 1);
+            assert_eq!(dao.get_program(0).unwrap().title, program.title);
+            assert!(dao.legacy_programs.is_empty());
+
+            let proposal = dao.get_proposal(0).unwrap();
+            assert_eq!((proposal.proposer, proposal.payment_amount_fiat, proposal.to), (mock_accounts.4, 15_000, mock_param.1));
+            assert_eq!(dao.get_proposal(1).unwrap().payment_amount_crypto, 200);
+            let milestone_amounts = |dao: &Dao, proposal_index: u32| dao.get_milestones(proposal_index).iter().map(|m| m.amount).collect::<Vec<u128>>();
+            assert_eq!(milestone_amounts(&dao, 0), vec![10_000, 5_000]);
+            assert_eq!(milestone_amounts(&dao, 1), vec![200]);
+
+            // New programs follow the migrated ones.
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.create_program("Title".to_string(), "Description".to_string(), 0, 1000, None), Ok(()));
+            assert_eq!(dao.get_program(2).unwrap().program_index, 2);
+        }
+
+        #[ink::test]
+        fn test_price_guards() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.set_price_guards(60_000, 500), Err(Error::MissingRole));
+            set_caller::<Environment>(mock_accounts.0);
+
+            // Guards are disabled by default.
+            assert_eq!(dao.get_price_guards(), (0, 0));
+
+            // Max price age: 1 minute, max price deviation: 5% from the last settled price
+            assert_eq!(dao.set_price_guards(60_000, 500), Ok(()));
+            assert_eq!(dao.get_price_guards(), (60_000, 500));
+
+            // No payout has settled a price yet.
+            assert_eq!(dao.get_settled_price("AZERO/USD".to_string()), None);
+        }
+
+        #[ink::test]
+        fn test_token_and_price_decimals() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+            let native_token = AccountId::from([0x00; 32]);
+            let stablecoin = AccountId::from([0x09; 32]);
+            let key: String = "AZERO/USD".to_string();
+
+            // Native token and DIA price defaults.
+            assert_eq!(dao.get_token_decimals(native_token), Ok(NATIVE_TOKEN_DECIMALS));
+            assert_eq!(dao.get_price_decimals(key.clone()), DEFAULT_PRICE_DECIMALS);
+
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.set_token_decimals(stablecoin, 6), Err(Error::MissingRole));
+            assert_eq!(dao.set_price_decimals(key.clone(), 8), Err(Error::MissingRole));
+            set_caller::<Environment>(mock_accounts.0);
+
+            assert_eq!(dao.set_token_decimals(stablecoin, 6), Ok(()));
+            assert_eq!(dao.set_price_decimals(key.clone(), 8), Ok(()));
+            assert_eq!(dao.get_token_decimals(stablecoin), Ok(6));
+            assert_eq!(dao.get_price_decimals(key.clone()), 8);
+            assert_eq!(dao.get_price_decimals("AZERO/EUR".to_string()), DEFAULT_PRICE_DECIMALS);
+
+            let emitted_events = recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 2);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[0].data[..])
+                .expect("encountered invalid contract event data buffer");
+            assert!(matches!(decoded_event, Event::TokenDecimalsChanged(TokenDecimalsChanged { token, decimals: 6 }) if token == stablecoin));
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            assert!(matches!(decoded_event, Event::PriceDecimalsChanged(PriceDecimalsChanged { cryto_fiat_key, decimals: 8 }) if cryto_fiat_key == key));
+        }
+
+        #[ink::test]
+        fn test_twap_window() {
+            let mut dao = init_dao();
+            let mock_accounts = get_mock_accounts();
+
+            // Fiat payouts settle at the spot price by default.
+            assert_eq!(dao.get_twap_window(), 0);
+
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.set_twap_window(3_600_000), Err(Error::MissingRole));
+            set_caller::<Environment>(mock_accounts.0);
+
+            assert_eq!(dao.set_twap_window(3_600_000), Ok(()));
+            assert_eq!(dao.get_twap_window(), 3_600_000);
+
+            let emitted_events = recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 1);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[0].data[..])
+                .expect("encountered invalid contract event data buffer");
+            assert!(matches!(decoded_event, Event::TwapWindowChanged(TwapWindowChanged { twap_window: 3_600_000 })));
+        }
+
+        #[ink::test]
+        fn test_vote_delegation() {
+            let mock_accounts = get_mock_accounts();
+            let mock_param = get_mock_proposal_params();
+            let third_member = AccountId::from([0x08; 32]);
+            let mut dao = Dao::new(
+                mock_accounts.5,
+                mock_accounts.0,
+                "Name".to_string(),
+                "Description".to_string(),
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                vec![],
+                vec![
+                    Step {
+                        step_index: 0,
+                        title: "Step 1".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                ],
+                vec![vec![mock_accounts.1, mock_accounts.2, third_member]],
+                vec![mock_accounts.3],
+                100,
+                100,
+                vec![mock_accounts.4],
+                false,
+                true,
+            ).unwrap();
+
+            // Only step members can delegate, to another member of the same step.
+            set_caller::<Environment>(mock_accounts.4);
+            assert_eq!(dao.delegate(0, mock_accounts.1), Err(Error::NotAllowVoting));
+            set_caller::<Environment>(mock_accounts.2);
+            assert_eq!(dao.delegate(1, mock_accounts.1), Err(Error::StepIndexOutOfBound));
+            assert_eq!(dao.delegate(0, mock_accounts.2), Err(Error::InvalidDelegate));
+            assert_eq!(dao.delegate(0, mock_accounts.4), Err(Error::InvalidDelegate));
+            assert_eq!(dao.undelegate(0), Err(Error::NotDelegated));
+            assert_eq!(dao.delegate(0, mock_accounts.1), Ok(()));
+            assert_eq!(dao.delegate(0, mock_accounts.1), Err(Error::AlreadyDelegated));
+            assert_eq!(dao.get_delegate(0, mock_accounts.2), Some(mock_accounts.1));
+            assert_eq!(dao.get_delegators(0, mock_accounts.1), vec![mock_accounts.2]);
+
+            // No delegation chains.
+            set_caller::<Environment>(third_member);
+            assert_eq!(dao.delegate(0, mock_accounts.2), Err(Error::InvalidDelegate));
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.delegate(0, third_member), Err(Error::InvalidDelegate));
+
+            set_caller::<Environment>(mock_accounts.0);
+            let _ = dao.create_program(
+                "Program Title".to_string(),
+                "Program Description".to_string(),
+                0,
+                1000 * 1000,
+                None
+            );
+            set_caller::<Environment>(mock_accounts.4);
+            let _ = dao.create_proposal(
+                0,
+                "Title".to_string(),
+                "Description".to_string(),
+                0,
+                1000 * 1000,
+                false,
+                0,
+                "AZERO/USD".to_string(),
+                200,
+                mock_param.0,
+                mock_param.1,
+                true
+            );
+
+            // The delegator can not vote directly while delegated.
+            set_caller::<Environment>(mock_accounts.2);
+            assert_eq!(dao.voting(0, 0, 1), Err(Error::VoteDelegated));
+
+            // The delegate votes with its own and the delegated weight.
+            set_caller::<Environment>(mock_accounts.1);
+            assert_eq!(dao.voting(0, 0, 2), Ok(()));
+            assert_eq!(dao.get_steps_voting_status(0)[0].disagree, 2);
+            assert_eq!(dao.get_member_voted(mock_accounts.1, 0, 0), (2, None));
+            assert_eq!(dao.get_member_voted(mock_accounts.2, 0, 0), (2, Some(mock_accounts.1)));
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            assert_voted_event(&emitted_events[emitted_events.len() - 1], 0, 0, mock_accounts.1, 2, 2);
+
+            // Revoting moves the delegated vote too.
+            assert_eq!(dao.voting(0, 0, 1), Ok(()));
+            let voting_status = dao.get_steps_voting_status(0)[0].clone();
+            assert_eq!((voting_status.agree, voting_status.disagree), (2, 0));
+
+            // After undelegating, the member overrides the vote cast by the delegate.
+            set_caller::<Environment>(mock_accounts.2);
+            assert_eq!(dao.undelegate(0), Ok(()));
+            assert!(dao.get_delegators(0, mock_accounts.1).is_empty());
+            assert_eq!(dao.voting(0, 0, 2), Ok(()));
+            assert_eq!(dao.get_member_voted(mock_accounts.2, 0, 0), (2, None));
+            let voting_status = dao.get_steps_voting_status(0)[0].clone();
+            assert_eq!((voting_status.agree, voting_status.disagree), (1, 1));
+
+            // Removing a step member clears its delegations.
+            set_caller::<Environment>(third_member);
+            assert_eq!(dao.delegate(0, mock_accounts.1), Ok(()));
+            set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao.remove_step_members(0, mock_accounts.1), Ok(()));
+            assert_eq!(dao.get_delegate(0, third_member), None);
+            assert!(dao.get_delegators(0, mock_accounts.1).is_empty());
+        }
+    }
+
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    #[cfg_attr(all(test, feature = "e2e-tests"), allow(unused_imports))]
+    mod e2e_tests {
+        use super::*;
+        use dia_oracle_setter::OracleSetters;
+        use dia_oracle::TokenPriceStorageRef;
+        use psp22::PSP22;
+        use psp22_token::Psp22TokenRef;
+        use ink_e2e::build_message;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        #[ink_e2e::test]
+        async fn test_oracle_integration(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let admin_acc = AccountId::from([0x01; 32]);
+            let step1_member = AccountId::from([0x02; 32]);
+            let step2_member = AccountId::from([0x03; 32]);
+            let whitelisted_contributor = AccountId::from([0x04; 32]);
+            let normal_member = AccountId::from([0x05; 32]);
+            
+            // init Oracle contract
+            // AZERO/USD - 2024/23/01
+            const PRICE: u128 = 1_071_576_625_798_566_000;
+
+            let constructor = TokenPriceStorageRef::new();
+            let contract_acc_id = client
+                .instantiate("dia_oracle", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            //init DAO
+            let dao_contructor = DaoRef::new(
+                contract_acc_id,
+                admin_acc,
+                "Name".to_string(),
+                "Description".to_string(),
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                vec!["twitter".to_string(), "facebook".to_string()],
+                vec![
+                    Step {
+                        step_index: 0,
+                        title: "Step 1".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                    Step {
+                        step_index: 1,
+                        title: "Step 2".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                ],
+                vec![vec![step1_member], vec![step2_member]],
+                vec![whitelisted_contributor],
+                100,
+                100,
+                vec![normal_member],
+                false,
+                false,
+            );
+
+            let dao_contract_acc_id = client
+                .instantiate("dao", &ink_e2e::alice(), dao_contructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            // set price for oracle
+            let set_price_message = build_message::<TokenPriceStorageRef>(contract_acc_id.clone())
+                .call(|tps| tps.set_price("AZERO/USD".to_string(), PRICE));
+
+            let _set_price_res = client
+                .call(&ink_e2e::alice(), set_price_message, 0, None)
+                .await
+                .expect("set failed");
+
+            let get_price_message = build_message::<TokenPriceStorageRef>(contract_acc_id.clone())
+                .call(|tps| tps.get_latest_price("AZERO/USD".to_string()));
+
+            // Get the latest price from Oracle.
+            let get_price_res = client
+                .call(&ink_e2e::alice(), get_price_message, 0, None)
+                .await
+                .expect("get failed");
+    
+            let latest_price = get_price_res.return_value().expect("Value is None").1;
+            assert_eq!(latest_price, PRICE);
+
+            // get Oracle asset price from DAO contract
+
+            let get_price_message_example =
+                build_message::<DaoRef>(dao_contract_acc_id.clone())
+                    .call(|dao| dao.get_price("AZERO/USD".to_string()));
+
+            let get_price_res_example = client
+                .call(&ink_e2e::alice(), get_price_message_example, 0, None)
+                .await
+                .expect("get failed");
+
+            let latest_price = get_price_res_example
+                .return_value()
+                .expect("Value is None")
+                .1;
+            assert_eq!(latest_price, PRICE);
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn test_stale_price(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let admin_acc = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let step1_member = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let normal_member = ink_e2e::account_id(ink_e2e::AccountKeyring::Dave);
+            let recipient = AccountId::from([0x07; 32]);
+            const PRICE: u128 = 1_071_576_625_798_566_000;
+
+            let constructor = TokenPriceStorageRef::new();
+            let oracle_acc_id = client
+                .instantiate("dia_oracle", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let dao_contructor = DaoRef::new(
+                oracle_acc_id,
+                admin_acc,
+                "Name".to_string(),
+                "Description".to_string(),
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                vec![],
+                vec![
+                    Step {
+                        step_index: 0,
+                        title: "Step 1".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                ],
+                vec![vec![step1_member]],
+                vec![],
+                100,
+                100,
+                vec![normal_member],
+                false,
+                false,
+            );
+            let dao_contract_acc_id = client
+                .instantiate("dao", &ink_e2e::alice(), dao_contructor, 1_000_000_000_000, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            // The price is set once, blocks of the next calls advance the block timestamp.
+            let set_price_message = build_message::<TokenPriceStorageRef>(oracle_acc_id.clone())
+                .call(|tps| tps.set_price("AZERO/USD".to_string(), PRICE));
+            client
+                .call(&ink_e2e::alice(), set_price_message, 0, None)
+                .await
+                .expect("set price failed");
+
+            let set_price_guards_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.set_price_guards(1, 0));
+            client
+                .call(&ink_e2e::alice(), set_price_guards_message, 0, None)
+                .await
+                .expect("set price guards failed");
+
+            let create_program_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.create_program("Program Title".to_string(), "Program Description".to_string(), 0, u64::MAX, None));
+            client
+                .call(&ink_e2e::alice(), create_program_message, 0, None)
+                .await
+                .expect("create program failed");
+
+            let create_proposal_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.create_proposal(
+                    0,
+                    "Title".to_string(),
+                    "Description".to_string(),
+                    0,
+                    u64::MAX,
+                    true,
+                    100,
+                    "AZERO/USD".to_string(),
+                    0,
+                    AccountId::from([0x00; 32]),
+                    recipient,
+                    true
+                ));
+            client
+                .call(&ink_e2e::dave(), create_proposal_message, 0, None)
+                .await
+                .expect("create proposal failed");
+
+            let voting_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.voting(0, 0, 1));
+            client
+                .call(&ink_e2e::bob(), voting_message, 0, None)
+                .await
+                .expect("voting failed");
+
+            // The price is older than 1 millisecond.
+            let execute_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.execute_proposal(0));
+            let execute_res = client
+                .call_dry_run(&ink_e2e::alice(), &execute_message, 0, None)
+                .await;
+            assert_eq!(execute_res.return_value(), Err(Error::StalePrice));
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn test_price_deviation(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let admin_acc = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let step1_member = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let normal_member = ink_e2e::account_id(ink_e2e::AccountKeyring::Dave);
+            let recipient = AccountId::from([0x07; 32]);
+            const PRICE: u128 = 1_000_000_000_000_000_000;
+
+            let constructor = TokenPriceStorageRef::new();
+            let oracle_acc_id = client
+                .instantiate("dia_oracle", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let dao_contructor = DaoRef::new(
+                oracle_acc_id,
+                admin_acc,
+                "Name".to_string(),
+                "Description".to_string(),
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                vec![],
+                vec![
+                    Step {
+                        step_index: 0,
+                        title: "Step 1".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                ],
+                vec![vec![step1_member]],
+                vec![],
+                100,
+                100,
+                vec![normal_member],
+                false,
+                false,
+            );
+            let dao_contract_acc_id = client
+                .instantiate("dao", &ink_e2e::alice(), dao_contructor, 1_000_000_000_000, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let set_price_message = build_message::<TokenPriceStorageRef>(oracle_acc_id.clone())
+                .call(|tps| tps.set_price("AZERO/USD".to_string(), PRICE));
+            client
+                .call(&ink_e2e::alice(), set_price_message, 0, None)
+                .await
+                .expect("set price failed");
+
+            // Max price deviation: 5%
+            let set_price_guards_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.set_price_guards(0, 500));
+            client
+                .call(&ink_e2e::alice(), set_price_guards_message, 0, None)
+                .await
+                .expect("set price guards failed");
+
+            let create_program_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.create_program("Program Title".to_string(), "Program Description".to_string(), 0, u64::MAX, None));
+            client
+                .call(&ink_e2e::alice(), create_program_message, 0, None)
+                .await
+                .expect("create program failed");
+
+            // Two passed fiat proposals.
+            for proposal_index in 0..2u32 {
+                let create_proposal_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                    .call(|dao| dao.create_proposal(
+                        0,
+                        "Title".to_string(),
+                        "Description".to_string(),
+                        0,
+                        u64::MAX,
+                        true,
+                        100,
+                        "AZERO/USD".to_string(),
+                        0,
+                        AccountId::from([0x00; 32]),
+                        recipient,
+                        true
+                    ));
+                client
+                    .call(&ink_e2e::dave(), create_proposal_message, 0, None)
+                    .await
+                    .expect("create proposal failed");
+
+                let voting_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                    .call(|dao| dao.voting(proposal_index, 0, 1));
+                client
+                    .call(&ink_e2e::bob(), voting_message, 0, None)
+                    .await
+                    .expect("voting failed");
+            }
+
+            // The first payout settles the price.
+            let execute_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.execute_proposal(0));
+            let execute_res = client
+                .call(&ink_e2e::alice(), execute_message, 0, None)
+                .await
+                .expect("execute proposal failed");
+            assert_eq!(execute_res.return_value(), Ok(()));
+
+            let get_settled_price_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.get_settled_price("AZERO/USD".to_string()));
+            let get_settled_price_res = client
+                .call_dry_run(&ink_e2e::alice(), &get_settled_price_message, 0, None)
+                .await;
+            assert_eq!(get_settled_price_res.return_value(), Some(PRICE));
+
+            // The price moves by more than 5%.
+            let set_price_message = build_message::<TokenPriceStorageRef>(oracle_acc_id.clone())
+                .call(|tps| tps.set_price("AZERO/USD".to_string(), PRICE / 1000 * 1051));
+            client
+                .call(&ink_e2e::alice(), set_price_message, 0, None)
+                .await
+                .expect("set price failed");
+
+            let execute_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.execute_proposal(1));
+            let execute_res = client
+                .call_dry_run(&ink_e2e::alice(), &execute_message, 0, None)
+                .await;
+            assert_eq!(execute_res.return_value(), Err(Error::PriceDeviation));
+
+            // The price moves back within 5% of the settled price.
+            let set_price_message = build_message::<TokenPriceStorageRef>(oracle_acc_id.clone())
+                .call(|tps| tps.set_price("AZERO/USD".to_string(), PRICE / 100 * 95));
+            client
+                .call(&ink_e2e::alice(), set_price_message, 0, None)
+                .await
+                .expect("set price failed");
+
+            let execute_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.execute_proposal(1));
+            let execute_res = client
+                .call_dry_run(&ink_e2e::alice(), &execute_message, 0, None)
+                .await;
+            assert_eq!(execute_res.return_value(), Ok(()));
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn test_cross_rate_payout(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let admin_acc = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let step1_member = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let normal_member = ink_e2e::account_id(ink_e2e::AccountKeyring::Dave);
+            let recipient = AccountId::from([0x07; 32]);
+            // AZERO/USD 0.55 and EUR/USD 1.10, so AZERO/EUR is 0.50
+            const AZERO_USD_PRICE: u128 = 550_000_000_000_000_000;
+            const EUR_USD_PRICE: u128 = 1_100_000_000_000_000_000;
+
+            let constructor = TokenPriceStorageRef::new();
+            let oracle_acc_id = client
+                .instantiate("dia_oracle", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let dao_contructor = DaoRef::new(
+                oracle_acc_id,
+                admin_acc,
+                "Name".to_string(),
+                "Description".to_string(),
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                vec![],
+                vec![
+                    Step {
+                        step_index: 0,
+                        title: "Step 1".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
+                    },
+                ],
+                vec![vec![step1_member]],
+                vec![],
+                100,
+                100,
+                vec![normal_member],
+                false,
+                false,
+            );
+            let dao_contract_acc_id = client
+                .instantiate("dao", &ink_e2e::alice(), dao_contructor, 1_000_000_000_000_000, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let create_program_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.create_program("Program Title".to_string(), "Program Description".to_string(), 0, u64::MAX, None));
+            client
+                .call(&ink_e2e::alice(), create_program_message, 0, None)
+                .await
+                .expect("create program failed");
+
+            // 11.00 EUR
+            let create_proposal_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.create_proposal(
+                    0,
+                    "Title".to_string(),
+                    "Description".to_string(),
+                    0,
+                    u64::MAX,
+                    true,
+                    1_100,
+                    "AZERO/EUR".to_string(),
+                    0,
+                    AccountId::from([0x00; 32]),
+                    recipient,
+                    true
+                ));
+
+            // The oracle has no AZERO/EUR pair and no EUR/USD price yet.
+            let create_proposal_res = client
+                .call_dry_run(&ink_e2e::dave(), &create_proposal_message, 0, None)
+                .await;
+            assert_eq!(create_proposal_res.return_value(), Err(Error::CouldNotGetOraclePrice));
+
+            let set_prices_message = build_message::<TokenPriceStorageRef>(oracle_acc_id.clone())
+                .call(|tps| tps.set_prices(vec![
+                    ("AZERO/USD".to_string(), AZERO_USD_PRICE),
+                    ("EUR/USD".to_string(), EUR_USD_PRICE),
+                ]));
+            client
+                .call(&ink_e2e::alice(), set_prices_message, 0, None)
+                .await
+                .expect("set prices failed");
+
+            client
+                .call(&ink_e2e::dave(), create_proposal_message, 0, None)
+                .await
+                .expect("create proposal failed");
+
+            let get_amount_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.get_proposal_payment_amount_from_oracle(0));
+            let get_amount_res = client
+                .call_dry_run(&ink_e2e::alice(), &get_amount_message, 0, None)
+                .await;
+            // 11.00 EUR at 0.50 EUR per AZERO is 22 AZERO.
+            assert_eq!(get_amount_res.return_value().map(|(amount, _)| amount), Ok(22_000_000_000_000));
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn test_psp22_payout(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let admin_acc = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let step1_member = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let step2_member = ink_e2e::account_id(ink_e2e::AccountKeyring::Charlie);
+            let normal_member = ink_e2e::account_id(ink_e2e::AccountKeyring::Dave);
+            let whitelisted_contributor = AccountId::from([0x04; 32]);
+            let oracle_address = AccountId::from([0x00; 32]);
+            let recipient = AccountId::from([0x07; 32]);
+            const AMOUNT: u128 = 200;
+
+            // init PSP22 token contract, Alice holds the whole supply.
+            let token_constructor = Psp22TokenRef::new(1_000_000);
+            let token_acc_id = client
+                .instantiate("psp22_token", &ink_e2e::alice(), token_constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
 
-        fn init_dao() -> Dao {
-            let mock_accounts = get_mock_accounts();
-            let dao = Dao::new(
-                mock_accounts.5,
-                mock_accounts.0,
+            //init DAO
+            let dao_contructor = DaoRef::new(
+                oracle_address,
+                admin_acc,
                 "Name".to_string(),
                 "Description".to_string(),
                 "Website".to_string(),
@@ -831,6 +5747,9 @@ pub mod dao {
                         use_default_settings: true,
                         quorum: 0,
                         threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
                     },
                     Step {
                         step_index: 1,
@@ -838,195 +5757,260 @@ pub mod dao {
                         use_default_settings: true,
                         quorum: 0,
                         threshold: 0,
+                        voting_mode: VotingMode::OneMemberOneVote,
+                        voting_token: AccountId::from([0x00; 32]),
+                        voting_window: 0,
                     },
                 ],
-                vec![vec![mock_accounts.1], vec![mock_accounts.2]],
-                vec![mock_accounts.3],
+                vec![vec![step1_member], vec![step2_member]],
+                vec![whitelisted_contributor],
                 100,
                 100,
-                vec![mock_accounts.4],
+                vec![normal_member],
                 false,
                 false,
             );
-            dao
-        }
-
-        #[ink::test]
-        fn test_init_dao() {
-            let dao = init_dao();
-            let info = dao.get_info();
-            assert_eq!(info.2, "Name".to_string());
-            assert_eq!(info.8.len(), 2);
-        }
-
-        #[ink::test]
-        fn test_create_program_success() {
-            let mut dao = init_dao();
-            let mock_accounts = get_mock_accounts();
-            set_caller::<Environment>(mock_accounts.0);
-            let _ = dao.create_program(
-                "Program Title".to_string(),
-                "Program Description".to_string(),
-                0,
-                1000 * 1000
-            );
-
-            let program = dao.get_program(0);
-            assert!(program.is_some(), "Fail to create new program");
-        }
 
-        #[ink::test]
-        fn test_create_program_fail() {
-            let mut dao = init_dao();
-            let mock_accounts = get_mock_accounts();
-            set_caller::<Environment>(mock_accounts.1);
+            let dao_contract_acc_id = client
+                .instantiate("dao", &ink_e2e::alice(), dao_contructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
 
-            let result: Result<(), Error> = dao.create_program(
-                "Program Title".to_string(),
-                "Program Description".to_string(),
-                0,
-                1000 * 1000
-            );
+            // Send PSP22 tokens to the DAO treasury.
+            let transfer_message = build_message::<Psp22TokenRef>(token_acc_id.clone())
+                .call(|token| token.transfer(dao_contract_acc_id.clone(), AMOUNT, Vec::new()));
+            client
+                .call(&ink_e2e::alice(), transfer_message, 0, None)
+                .await
+                .expect("transfer failed");
 
-            assert!(result.is_err(), "Only admin can create programs");
-        }
-        
-        #[ink::test] 
-        fn test_create_proposal() {
-            let mut dao = init_dao();
-            let mock_param = get_mock_proposal_params();
-            let mock_accounts = get_mock_accounts();
-            set_caller::<Environment>(mock_accounts.0);
-            // Create program
-            let _ = dao.create_program(
-                "Program Title".to_string(),
-                "Program Description".to_string(),
-                0,
-                1000 * 1000
-            );
+            // Create program & proposal paid in the PSP22 token.
+            let create_program_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.create_program("Program Title".to_string(), "Program Description".to_string(), 0, u64::MAX, None));
+            client
+                .call(&ink_e2e::alice(), create_program_message, 0, None)
+                .await
+                .expect("create program failed");
+
+            let create_proposal_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.create_proposal(
+                    0,
+                    "Title".to_string(),
+                    "Description".to_string(),
+                    0,
+                    u64::MAX,
+                    false,
+                    0,
+                    "AZERO/USD".to_string(),
+                    AMOUNT,
+                    token_acc_id.clone(),
+                    recipient,
+                    true
+                ));
+            client
+                .call(&ink_e2e::dave(), create_proposal_message, 0, None)
+                .await
+                .expect("create proposal failed");
 
-            let program = dao.get_program(0);
-            assert!(program.is_some(), "Fail to create new program");
+            // Step members voting
+            let step1_voting_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.voting(0, 0, 1));
+            client
+                .call(&ink_e2e::bob(), step1_voting_message, 0, None)
+                .await
+                .expect("voting failed");
 
-            // Create proposal
+            let step2_voting_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.voting(0, 1, 1));
+            client
+                .call(&ink_e2e::charlie(), step2_voting_message, 0, None)
+                .await
+                .expect("voting failed");
 
-            set_caller::<Environment>(mock_accounts.4);
-            let result: Result<(), Error> = dao.create_proposal(
-                0,
-                "Title".to_string(),
-                "Description".to_string(),
-                0,
-                1000 * 1000,
-                false,
-                0,
-                "AZERO/USD".to_string(),
-                200,
-                mock_param.0,
-                mock_param.1,
-                true
-            );
+            // Execute proposal
+            let execute_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.execute_proposal(0));
+            let execute_res = client
+                .call(&ink_e2e::alice(), execute_message, 0, None)
+                .await
+                .expect("execute failed");
+            assert_eq!(execute_res.return_value(), Ok(()));
 
-            assert!(result.is_ok(), "Fail to create proposal");
+            // The beneficiary received PSP22 tokens from the DAO treasury.
+            let balance_message = build_message::<Psp22TokenRef>(token_acc_id.clone())
+                .call(|token| token.balance_of(recipient));
+            let balance_res = client
+                .call_dry_run(&ink_e2e::alice(), &balance_message, 0, None)
+                .await;
+            assert_eq!(balance_res.return_value(), AMOUNT);
 
+            Ok(())
         }
 
-        #[ink::test]
-        fn test_voting_and_execute_proposal() {
-            let mut dao = init_dao();
-            let mock_param = get_mock_proposal_params();
-            let mock_accounts = get_mock_accounts();
-            set_caller::<Environment>(mock_accounts.0);
-            // Create program
-            let _ = dao.create_program(
-                "Program Title".to_string(),
-                "Program Description".to_string(),
-                0,
-                1000 * 1000
-            );
+        #[ink_e2e::test]
+        async fn test_token_weighted_voting(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let admin_acc = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let step_member1 = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let step_member2 = ink_e2e::account_id(ink_e2e::AccountKeyring::Charlie);
+            let normal_member = ink_e2e::account_id(ink_e2e::AccountKeyring::Dave);
+            let oracle_address = AccountId::from([0x00; 32]);
+            let recipient = AccountId::from([0x07; 32]);
 
-            let program = dao.get_program(0);
-            assert!(program.is_some(), "Fail to create new program");
+            // init PSP22 voting token, Alice holds the whole supply.
+            let token_constructor = Psp22TokenRef::new(1_000_000);
+            let token_acc_id = client
+                .instantiate("psp22_token", &ink_e2e::alice(), token_constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
 
-            // Create proposal
+            // Bob holds 600 and Charlie 400 voting tokens.
+            for (member, amount) in [(step_member1, 600), (step_member2, 400)] {
+                let transfer_message = build_message::<Psp22TokenRef>(token_acc_id.clone())
+                    .call(|token| token.transfer(member, amount, Vec::new()));
+                client
+                    .call(&ink_e2e::alice(), transfer_message, 0, None)
+                    .await
+                    .expect("transfer failed");
+            }
 
-            set_caller::<Environment>(mock_accounts.4);
-            let result: Result<(), Error> = dao.create_proposal(
-                0,
-                "Title".to_string(),
+            //init DAO with a token weighted step, 50% quorum and 100% threshold
+            let dao_contructor = DaoRef::new(
+                oracle_address,
+                admin_acc,
+                "Name".to_string(),
                 "Description".to_string(),
-                0,
-                1000 * 1000,
+                "Website".to_string(),
+                "Email".to_string(),
+                "Address".to_string(),
+                vec![],
+                vec![
+                    Step {
+                        step_index: 0,
+                        title: "Step 1".to_string(),
+                        use_default_settings: true,
+                        quorum: 0,
+                        threshold: 0,
+                        voting_mode: VotingMode::TokenWeighted,
+                        voting_token: token_acc_id.clone(),
+                        voting_window: 0,
+                    },
+                ],
+                vec![vec![step_member1, step_member2]],
+                vec![],
+                50,
+                100,
+                vec![normal_member],
+                false,
                 false,
-                0,
-                "AZERO/USD".to_string(),
-                200,
-                mock_param.0,
-                mock_param.1,
-                true
             );
 
-            assert!(result.is_ok(), "Fail to create proposal");
-
-
-            // Step 0: member voting
-            set_caller::<Environment>(mock_accounts.1);
-            let _ = dao.voting(0, 0, 1);
-            let proposal_votings: Vec<ProposalVoting> = dao.get_steps_voting_status(0);
-            assert_eq!(proposal_votings[0].agree, 1);
-
-            // Step 1: member voting
-            set_caller::<Environment>(mock_accounts.2);
-            let _ = dao.voting(0, 1, 1);
-            let proposal_votings: Vec<ProposalVoting> = dao.get_steps_voting_status(0);
-            assert_eq!(proposal_votings[1].agree, 1);
-
-            let previous_balance = dao.get_contract_balance();
-
-            // execute proposal
-            let _ = dao.execute_proposal(0);
+            let dao_contract_acc_id = client
+                .instantiate("dao", &ink_e2e::alice(), dao_contructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
 
-            // Check balance after execution
-            let after_balance = dao.get_contract_balance();
-            let proposal = dao.get_proposal(0);
-            assert_eq!(proposal.is_some(), true);
-            assert_eq!(proposal.unwrap().executed, true);
-            assert_eq!(previous_balance - after_balance, 200)
-        } 
-    }
+            let create_program_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.create_program("Program Title".to_string(), "Program Description".to_string(), 0, u64::MAX, None));
+            client
+                .call(&ink_e2e::alice(), create_program_message, 0, None)
+                .await
+                .expect("create program failed");
+
+            // Creating the proposal snapshots the token balances of step members.
+            let create_proposal_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.create_proposal(
+                    0,
+                    "Title".to_string(),
+                    "Description".to_string(),
+                    0,
+                    u64::MAX,
+                    false,
+                    0,
+                    "AZERO/USD".to_string(),
+                    100,
+                    AccountId::from([0x00; 32]),
+                    recipient,
+                    true
+                ));
+            let create_proposal_res = client
+                .call(&ink_e2e::dave(), create_proposal_message, 0, None)
+                .await
+                .expect("create proposal failed");
+            assert_eq!(create_proposal_res.return_value(), Ok(()));
+
+            // Tokens moved after the snapshot do not change the voting weights.
+            let transfer_message = build_message::<Psp22TokenRef>(token_acc_id.clone())
+                .call(|token| token.transfer(step_member2, 600, Vec::new()));
+            client
+                .call(&ink_e2e::bob(), transfer_message, 0, None)
+                .await
+                .expect("transfer failed");
+
+            let weight_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.get_voting_weight(0, 0, step_member1));
+            let weight_res = client
+                .call_dry_run(&ink_e2e::alice(), &weight_message, 0, None)
+                .await;
+            assert_eq!(weight_res.return_value(), 600);
+
+            // 400 of 1000 voting tokens: the quorum is not reached.
+            let voting_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.voting(0, 0, 1));
+            client
+                .call(&ink_e2e::charlie(), voting_message, 0, None)
+                .await
+                .expect("voting failed");
+
+            let status_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.get_proposal_status(0));
+            let status_res = client
+                .call_dry_run(&ink_e2e::alice(), &status_message, 0, None)
+                .await;
+            assert_eq!(status_res.return_value(), Some(ProposalStatus::Active));
+
+            let voting_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.voting(0, 0, 1));
+            client
+                .call(&ink_e2e::bob(), voting_message, 0, None)
+                .await
+                .expect("voting failed");
 
+            let voting_status_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.get_steps_voting_status(0));
+            let voting_status_res = client
+                .call_dry_run(&ink_e2e::alice(), &voting_status_message, 0, None)
+                .await;
+            assert_eq!(voting_status_res.return_value()[0].agree, 1000);
 
-    #[cfg(all(test, feature = "e2e-tests"))]
-    #[cfg_attr(all(test, feature = "e2e-tests"), allow(unused_imports))]
-    mod e2e_tests {
-        use super::*;
-        use dia_oracle_setter::OracleSetters;
-        use dia_oracle::TokenPriceStorageRef;
-        use ink_e2e::build_message;
+            let status_res = client
+                .call_dry_run(&ink_e2e::alice(), &status_message, 0, None)
+                .await;
+            assert_eq!(status_res.return_value(), Some(ProposalStatus::Passed));
 
-        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+            Ok(())
+        }
 
         #[ink_e2e::test]
-        async fn test_oracle_integration(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        async fn test_psp22_fund(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
             let admin_acc = AccountId::from([0x01; 32]);
-            let step1_member = AccountId::from([0x02; 32]);
-            let step2_member = AccountId::from([0x03; 32]);
-            let whitelisted_contributor = AccountId::from([0x04; 32]);
-            let normal_member = AccountId::from([0x05; 32]);
-            
-            // init Oracle contract
-            // AZERO/USD - 2024/23/01
-            const PRICE: u128 = 1_071_576_625_798_566_000;
-
-            let constructor = TokenPriceStorageRef::new();
-            let contract_acc_id = client
-                .instantiate("dia_oracle", &ink_e2e::alice(), constructor, 0, None)
+            let whitelisted_contributor = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let oracle_address = AccountId::from([0x00; 32]);
+            const AMOUNT: u128 = 200;
+
+            // init PSP22 token contract, Alice holds the whole supply.
+            let token_constructor = Psp22TokenRef::new(1_000_000);
+            let token_acc_id = client
+                .instantiate("psp22_token", &ink_e2e::alice(), token_constructor, 0, None)
                 .await
                 .expect("instantiate failed")
                 .account_id;
 
             //init DAO
             let dao_contructor = DaoRef::new(
-                contract_acc_id,
+                oracle_address,
                 admin_acc,
                 "Name".to_string(),
                 "Description".to_string(),
@@ -1034,27 +6018,12 @@ pub mod dao {
                 "Email".to_string(),
                 "Address".to_string(),
                 vec!["twitter".to_string(), "facebook".to_string()],
-                vec![
-                    Step {
-                        step_index: 0,
-                        title: "Step 1".to_string(),
-                        use_default_settings: true,
-                        quorum: 0,
-                        threshold: 0,
-                    },
-                    Step {
-                        step_index: 1,
-                        title: "Step 2".to_string(),
-                        use_default_settings: true,
-                        quorum: 0,
-                        threshold: 0,
-                    },
-                ],
-                vec![vec![step1_member], vec![step2_member]],
+                vec![],
+                vec![],
                 vec![whitelisted_contributor],
                 100,
                 100,
-                vec![normal_member],
+                vec![],
                 false,
                 false,
             );
@@ -1065,43 +6034,37 @@ pub mod dao {
                 .expect("instantiate failed")
                 .account_id;
 
-            // set price for oracle
-            let set_price_message = build_message::<TokenPriceStorageRef>(contract_acc_id.clone())
-                .call(|tps| tps.set_price("AZERO/USD".to_string(), PRICE));
-
-            let _set_price_res = client
-                .call(&ink_e2e::alice(), set_price_message, 0, None)
-                .await
-                .expect("set failed");
-
-            let get_price_message = build_message::<TokenPriceStorageRef>(contract_acc_id.clone())
-                .call(|tps| tps.get_latest_price("AZERO/USD".to_string()));
-
-            // Get the latest price from Oracle.
-            let get_price_res = client
-                .call(&ink_e2e::alice(), get_price_message, 0, None)
+            // Approve the DAO to pull tokens, then fund.
+            let approve_message = build_message::<Psp22TokenRef>(token_acc_id.clone())
+                .call(|token| token.approve(dao_contract_acc_id.clone(), AMOUNT));
+            client
+                .call(&ink_e2e::alice(), approve_message, 0, None)
                 .await
-                .expect("get failed");
-    
-            let latest_price = get_price_res.return_value().expect("Value is None").1;
-            assert_eq!(latest_price, PRICE);
-
-            // get Oracle asset price from DAO contract
-
-            let get_price_message_example =
-                build_message::<DaoRef>(dao_contract_acc_id.clone())
-                    .call(|dao| dao.get_price("AZERO/USD".to_string()));
+                .expect("approve failed");
 
-            let get_price_res_example = client
-                .call(&ink_e2e::alice(), get_price_message_example, 0, None)
+            let fund_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.fund_token(token_acc_id.clone(), AMOUNT));
+            let fund_res = client
+                .call(&ink_e2e::alice(), fund_message, 0, None)
                 .await
-                .expect("get failed");
+                .expect("fund failed");
+            assert_eq!(fund_res.return_value(), Ok(()));
+
+            // Check the treasury ledger and the DAO token balance.
+            let contribution_message = build_message::<DaoRef>(dao_contract_acc_id.clone())
+                .call(|dao| dao.get_contribution(whitelisted_contributor, token_acc_id.clone()));
+            let contribution_res = client
+                .call_dry_run(&ink_e2e::alice(), &contribution_message, 0, None)
+                .await;
+            assert_eq!(contribution_res.return_value(), AMOUNT);
+
+            let balance_message = build_message::<Psp22TokenRef>(token_acc_id.clone())
+                .call(|token| token.balance_of(dao_contract_acc_id.clone()));
+            let balance_res = client
+                .call_dry_run(&ink_e2e::alice(), &balance_message, 0, None)
+                .await;
+            assert_eq!(balance_res.return_value(), AMOUNT);
 
-            let latest_price = get_price_res_example
-                .return_value()
-                .expect("Value is None")
-                .1;
-            assert_eq!(latest_price, PRICE);
             Ok(())
         }
     }
@@ -23,5 +23,59 @@ pub enum Error {
     ProgramHasNotStarted,
     ProgramHasEnded,
     ContributorExisted,
-    ProposalHasExecuted
+    ProposalHasExecuted,
+    TokenTransferFailed,
+    InvalidToken,
+    DaoPaused,
+    NotAdminOrOwner,
+    NotProposer,
+    VotingHasStarted,
+    MilestoneAmountExceeded,
+    MilestoneIndexOutOfBound,
+    ProposalHasMilestones,
+    ProposalHasNotPassed,
+    NotGrantee,
+    MilestoneHasSubmitted,
+    MilestoneNotSubmitted,
+    StepIndexOutOfBound,
+    VoteDelegated,
+    InvalidDelegate,
+    AlreadyDelegated,
+    NotDelegated,
+    ProposalHasCancelled,
+    ProposalHasRejected,
+    ProposalHasExpired,
+    InvalidProposalStatus,
+    StepNotActive,
+    NotOwner,
+    UpgradeFailed,
+    StalePrice,
+    PriceDeviation,
+    InvalidPrice,
+    ConversionOverflow,
+    UnknownTokenDecimals,
+    InvalidPairKey,
+    TransferFailed,
+    NotPendingOwner,
+    NotPendingAdmin,
+    MissingRole,
+    RoleExisted,
+    ExceedProgramBudget,
+    ProgramTokenMismatch,
+    InvalidProgramBudget,
+    InvalidProgramDates,
+    ProgramClosed,
+    ProgramNotClosed,
+    ProgramArchived,
+    TitleTooLong,
+    DescriptionTooLong,
+    TooManySocialAccounts,
+    SocialAccountTooLong,
+    InvalidContentCid,
+    InvalidProposalDates,
+    ProposalOutsideProgram,
+    ZeroPaymentAmount,
+    ZeroBeneficiary,
+    ArithmeticOverflow,
+    TokenBalanceFailed
 }
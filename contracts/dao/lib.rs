@@ -1,11 +1,16 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
+// The DAO messages mirror the DAO settings, so they take many arguments
+// and return wide tuples.
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
 pub mod types;
 pub mod errors;
+pub mod conversion;
 pub mod dao_contract;
 
 pub use types::*;
 pub use errors::*;
+pub use conversion::*;
 pub use dao_contract::dao::*;
 
 
@@ -1,8 +1,8 @@
-use ink::primitives::{AccountId};
+use ink::primitives::{AccountId, Hash};
 use ink::prelude::string::String;
 
 
-#[derive(scale::Decode, scale::Encode, Debug, Clone)]
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Default)]
 #[cfg_attr(
     feature = "std",
     derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
@@ -15,6 +15,64 @@ pub struct Program {
     pub end_date: u64,
 }
 
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ProgramState {
+    #[default]
+    Open,
+    // No new proposals, existing proposals can still be voted and executed
+    Closed,
+    // Closed and read-only, the unused budget is returned to the treasury
+    Archived,
+}
+
+// Budget of a funding program, allocated from the DAO treasury.
+// committed: reserved by proposals from their creation, including paid amounts,
+// paid: transferred to grantees.
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ProgramBudget {
+    pub token: AccountId,
+    pub amount: u128,
+    pub committed: u128,
+    pub paid: u128,
+}
+
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum VotingMode {
+    // Each step member has one vote
+    #[default]
+    OneMemberOneVote,
+    // Each step member has a weight set by the DAO admin, 1 by default
+    FixedWeight,
+    // Each step member votes with its voting token balance,
+    // snapshotted when a proposal is created
+    TokenWeighted
+}
+
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum WorkflowMode {
+    // All workflow steps vote at the same time
+    #[default]
+    Parallel,
+    // A step votes only once the previous step has passed
+    Sequential
+}
+
 #[derive(scale::Decode, scale::Encode, Debug, Clone)]
 #[cfg_attr(
     feature = "std",
@@ -26,6 +84,33 @@ pub struct Step {
     pub use_default_settings: bool,
     pub quorum: u8,
     pub threshold: u8,
+    pub voting_mode: VotingMode,
+    // PSP22 token used by the TokenWeighted voting mode
+    pub voting_token: AccountId,
+    // Voting window of the step in the sequential workflow mode, starting when
+    // the previous step passes. 0 means until the proposal voting ends.
+    pub voting_window: u64,
+}
+
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ProposalStatus {
+    // Voting has not started
+    #[default]
+    Pending,
+    Active,
+    // Qualified all workflow steps, waiting for execution
+    Passed,
+    // Voting ended without qualifying all workflow steps
+    Rejected,
+    // Withdrawn by the proposer or cancelled by the admin
+    Cancelled,
+    Executed,
+    // Passed but not executed within the DAO execution period
+    Expired
 }
 
 #[derive(scale::Decode, scale::Encode, Debug, Clone)]
@@ -42,46 +127,156 @@ pub struct Proposal {
     pub start_date: u64,
     pub end_date: u64,
     pub use_fiat: bool,
-    pub payment_amount_fiat: u32,
+    // Fiat amount in minor units (cents), see FIAT_DECIMALS.
+    pub payment_amount_fiat: u128,
     pub cryto_fiat_key: String,
     pub payment_amount_crypto: u128,
     pub token: AccountId,
     pub to: AccountId,
     pub allow_early_executed: bool,
-    pub executed: bool
+    pub executed: bool,
+    // Only final states are stored, use get_proposal_status for the current status.
+    pub status: ProposalStatus
 }
 
+// Proposal layout of storage version 1, the fiat amount was in whole units.
+// Proposals are re-encoded as Proposal by `migrate`.
 #[derive(scale::Decode, scale::Encode, Debug, Clone)]
 #[cfg_attr(
     feature = "std",
     derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
 )]
-pub struct ProposalVoting {
-    pub agree: u32,
-    pub disagree: u32,
-    pub neutral: u32
+pub struct ProposalV1 {
+    pub program_index: u32,
+    pub proposal_index: u32,
+    pub proposer: AccountId,
+    pub title: String,
+    pub description: String,
+    pub start_date: u64,
+    pub end_date: u64,
+    pub use_fiat: bool,
+    pub payment_amount_fiat: u32,
+    pub cryto_fiat_key: String,
+    pub payment_amount_crypto: u128,
+    pub token: AccountId,
+    pub to: AccountId,
+    pub allow_early_executed: bool,
+    pub executed: bool,
+    pub status: ProposalStatus
 }
 
-impl Default for Program {
-    fn default() -> Self {
-        Self {
-            program_index: 0,
-            title: String::from(""),
-            description: String::from(""),
-            start_date: 0,
-            end_date: 0,
-        }
-    }
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum MilestoneState {
+    // Waiting for the grantee deliverables
+    #[default]
+    Pending,
+    // Deliverables submitted, step members are voting
+    Submitted,
+    // The milestone amount has been paid
+    Paid
 }
 
-impl Default for ProposalVoting {
-    fn default() -> Self {
-        Self {
-            agree: 0,
-            disagree: 0,
-            neutral: 0
-        }
-    }
+// A version of the proposal content, revision 0 is the created proposal.
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ProposalRevision {
+    pub revision: u32,
+    // Blake2x256 hash of the encoded title, description, payment, beneficiary and content CID
+    pub content_hash: Hash,
+    pub updated_at: u64,
+}
+
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Default)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Milestone {
+    pub milestone_index: u8,
+    pub title: String,
+    // Same unit as the proposal payment: fiat units if use_fiat, otherwise crypto units.
+    pub amount: u128,
+    pub due_date: u64,
+    pub state: MilestoneState,
+    // Deliverables submitted by the grantee, e.g. a link to a report.
+    pub deliverable: String
+}
+
+// Roles granted by the DAO admin to split the admin duties.
+// The admin holds every role.
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Role {
+    // Creates programs, cancels proposals and sets the proposal workflow
+    ProgramManager,
+    // Manages normal members, whitelisted contributors, step members and their weights
+    MembershipManager,
+    // Sets the price guards, decimals and TWAP window of fiat payouts
+    TreasuryManager,
+    // Pauses and unpauses the DAO
+    Pauser
+}
+
+// Groups of DAO members stored as indexed sets in the DAO storage.
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum MemberGroup {
+    NormalMember,
+    WhitelistedContributor,
+    // Members of a workflow step
+    StepMember(u8),
+    // Holders of a role
+    RoleMember(Role)
+}
+
+// Content which can be kept off-chain behind a content identifier (multihash or CID bytes).
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ContentKey {
+    DaoDescription,
+    SocialAccounts,
+    // Description of a program
+    Program(u32),
+    // Description of a proposal
+    Proposal(u32)
+}
+
+// Filters of the paginated proposal getter, None matches any value.
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Default)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct ProposalFilter {
+    pub proposer: Option<AccountId>,
+    pub program_index: Option<u32>,
+    pub executed: Option<bool>,
+    pub status: Option<ProposalStatus>
+}
+
+#[derive(scale::Decode, scale::Encode, Debug, Clone, Default)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ProposalVoting {
+    // Weighted tallies, equal to head counts in the OneMemberOneVote mode
+    pub agree: u128,
+    pub disagree: u128,
+    pub neutral: u128
 }
 
 impl Default for Step {
@@ -92,6 +287,9 @@ impl Default for Step {
             use_default_settings: true,
             quorum: 0,
             threshold: 0,
+            voting_mode: VotingMode::OneMemberOneVote,
+            voting_token: AccountId::from([0x00; 32]),
+            voting_window: 0,
         }
     }
 }
@@ -113,7 +311,8 @@ impl Default for Proposal {
             token: AccountId::from([0x00; 32]),
             to: AccountId::from([0x00; 32]),
             allow_early_executed: true,
-            executed: false
+            executed: false,
+            status: ProposalStatus::Pending
         }
     }
 }
\ No newline at end of file
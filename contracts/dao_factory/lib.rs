@@ -1,8 +1,12 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
+// create_dao mirrors the DAO constructor arguments.
+#![allow(clippy::too_many_arguments, clippy::large_enum_variant)]
 
 #[ink::contract]
 mod dao_factory {
     use dao::{DaoRef, Step};
+    use ink::codegen::TraitCallBuilder;
+    use ink::env::call::FromAccountId;
     use ink::prelude::vec::Vec;
     use ink::ToAccountId;
     use ink::prelude::string::String;
@@ -26,7 +30,49 @@ mod dao_factory {
         limited_number: u8,
         // True: anyone can create new DAO, False: Only whitelisted creators
         open: bool,
-        
+        // Account that can accept the DAOFactory ownership
+        pending_owner: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferStarted {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct DaoUpgraded {
+        #[ink(topic)]
+        dao: AccountId,
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct DaoUpgradeFailed {
+        #[ink(topic)]
+        dao: AccountId,
+        failure: DaoUpgradeFailure,
+    }
+
+    // Why upgrade_daos could not roll a DAO forward.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum DaoUpgradeFailure {
+        // The DAO code was not replaced, e.g. the DAO is no longer owned by the factory.
+        NotUpgraded,
+        // The DAO runs the new code on its old storage layout until it is migrated.
+        // Calling upgrade_daos again for the DAO retries the migration.
+        NotMigrated,
     }
 
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -38,6 +84,11 @@ mod dao_factory {
         DaoCreatorExisted,
         NotInWhitelistedCreators,
         ExceedLimitedDAONumber,
+        CouldNotUpgradeDAO,
+        NotPendingOwner,
+        CouldNotTransferDaoAdmin,
+        CouldNotTransferDaoOwnership,
+        InvalidDaoParams(dao::Error),
     }
 
     impl DaoFactory {
@@ -45,14 +96,15 @@ mod dao_factory {
         #[ink(constructor)]
         pub fn new(owner: AccountId, oracle_address: AccountId, dao_code_hash: Hash) -> Self {
             Self {
-                owner: owner,
-                oracle_address: oracle_address,
+                owner,
+                oracle_address,
                 daos: Vec::new(),
-                dao_code_hash: dao_code_hash,
+                dao_code_hash,
                 whitelisted_creators: Vec::new(),
                 num_creator_daos: Mapping::default(),
                 limited_number: 5,
-                open: true
+                open: true,
+                pending_owner: None,
             }
         }
 
@@ -78,10 +130,8 @@ mod dao_factory {
             let caller = Self::env().caller();
 
             // Check creation previlege
-            if !self.open {
-                if !self.whitelisted_creators.contains(&caller) {
-                    return Err(DaoFactoryError::NotInWhitelistedCreators);
-                }
+            if !self.open && !self.whitelisted_creators.contains(&caller) {
+                return Err(DaoFactoryError::NotInWhitelistedCreators);
             }
             // Check limited number of created DAOs
             let num_creator_daos: u8 = self.num_creator_daos.get(caller).unwrap_or_default();
@@ -90,7 +140,7 @@ mod dao_factory {
             }
 
             // Initial new DAO
-            let dao_ref = DaoRef::new(
+            let dao_ref: DaoRef = match DaoRef::new(
                 self.oracle_address,
                 Self::env().caller(),
                 name,
@@ -120,7 +170,12 @@ mod dao_factory {
                 ]
                 .concat()[..4],
             )
-            .try_instantiate().map_err(|_| DaoFactoryError::CouldNotCreateDAO).unwrap().unwrap();
+            .try_instantiate() {
+                Ok(Ok(Ok(dao_ref))) => dao_ref,
+                // The DAO constructor rejected the parameters.
+                Ok(Ok(Err(error))) => return Err(DaoFactoryError::InvalidDaoParams(error)),
+                _ => return Err(DaoFactoryError::CouldNotCreateDAO),
+            };
 
             let dao_address =
                 <DaoRef as ToAccountId<super::dao_factory::Environment>>::to_account_id(&dao_ref);
@@ -151,6 +206,114 @@ mod dao_factory {
             Ok(())
         }
 
+        // Roll created DAOs forward to the current DAO code hash and migrate their storage.
+        // DAOs are upgraded in batches of `limit` DAOs from the `offset` index to stay within the weight limit.
+        // The new code only runs from the next call into a DAO, so the migration is a second call
+        // and can fail after the code was replaced.
+        // A DAO which can not be rolled forward does not stop the batch:
+        // it is returned with its failure and reported with a DaoUpgradeFailed event.
+        #[ink(message)]
+        pub fn upgrade_daos(&mut self, offset: u32, limit: u32) -> Result<Vec<(AccountId, DaoUpgradeFailure)>, DaoFactoryError> {
+            // Only DAO Factory owner can do
+            if Self::env().caller() != self.owner {
+                return Err(DaoFactoryError::NotOwner);
+            }
+
+            let mut failed_daos: Vec<(AccountId, DaoUpgradeFailure)> = Vec::new();
+            for dao_address in self.daos.iter().skip(offset as usize).take(limit as usize) {
+                let mut dao_ref: DaoRef = FromAccountId::from_account_id(*dao_address);
+                let failure: Option<DaoUpgradeFailure> = if !matches!(dao_ref.call_mut().upgrade(self.dao_code_hash).try_invoke(), Ok(Ok(Ok(())))) {
+                    Some(DaoUpgradeFailure::NotUpgraded)
+                } else if !matches!(dao_ref.call_mut().migrate().try_invoke(), Ok(Ok(Ok(())))) {
+                    Some(DaoUpgradeFailure::NotMigrated)
+                } else {
+                    None
+                };
+
+                match failure {
+                    None => ink::codegen::EmitEvent::<DaoFactory>::emit_event(self.env(), DaoUpgraded {
+                        dao: *dao_address,
+                        code_hash: self.dao_code_hash,
+                    }),
+                    Some(failure) => {
+                        failed_daos.push((*dao_address, failure));
+                        ink::codegen::EmitEvent::<DaoFactory>::emit_event(self.env(), DaoUpgradeFailed {
+                            dao: *dao_address,
+                            failure,
+                        });
+                    }
+                }
+            }
+            Ok(failed_daos)
+        }
+
+        // Start transferring the DAOFactory ownership, the new owner has to accept it.
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), DaoFactoryError> {
+            // Only DAO Factory owner can do
+            if Self::env().caller() != self.owner {
+                return Err(DaoFactoryError::NotOwner);
+            }
+
+            self.pending_owner = Some(new_owner);
+            // The dao dependency also implements EmitEvent for the environment,
+            // so the event base of this contract is named explicitly.
+            ink::codegen::EmitEvent::<DaoFactory>::emit_event(self.env(), OwnershipTransferStarted {
+                previous_owner: self.owner,
+                new_owner,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<(), DaoFactoryError> {
+            let caller = Self::env().caller();
+            if self.pending_owner != Some(caller) {
+                return Err(DaoFactoryError::NotPendingOwner);
+            }
+
+            let previous_owner: AccountId = self.owner;
+            self.owner = caller;
+            self.pending_owner = None;
+            ink::codegen::EmitEvent::<DaoFactory>::emit_event(self.env(), OwnershipTransferred {
+                previous_owner,
+                new_owner: caller,
+            });
+            Ok(())
+        }
+
+        // The DAOFactory owns the DAOs it created, so its owner can replace
+        // a departing or compromised DAO admin. The new admin has to accept it.
+        #[ink(message)]
+        pub fn transfer_dao_admin(&mut self, dao_address: AccountId, new_admin: AccountId) -> Result<(), DaoFactoryError> {
+            // Only DAO Factory owner can do
+            if Self::env().caller() != self.owner {
+                return Err(DaoFactoryError::NotOwner);
+            }
+
+            let mut dao_ref: DaoRef = FromAccountId::from_account_id(dao_address);
+            match dao_ref.call_mut().transfer_admin(new_admin).try_invoke() {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(DaoFactoryError::CouldNotTransferDaoAdmin),
+            }
+        }
+
+        // The factory owner can hand a DAO over to its community, the new owner has to accept it.
+        // The factory can not upgrade a DAO it does not own anymore.
+        #[ink(message)]
+        pub fn transfer_dao_ownership(&mut self, dao_address: AccountId, new_owner: AccountId) -> Result<(), DaoFactoryError> {
+            // Only DAO Factory owner can do
+            if Self::env().caller() != self.owner {
+                return Err(DaoFactoryError::NotOwner);
+            }
+
+            let mut dao_ref: DaoRef = FromAccountId::from_account_id(dao_address);
+            match dao_ref.call_mut().transfer_ownership(new_owner).try_invoke() {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(DaoFactoryError::CouldNotTransferDaoOwnership),
+            }
+        }
+
         #[ink(message)]
         pub fn add_whitelisted_creator(&mut self, creator: AccountId) -> Result<(), DaoFactoryError> {
             
@@ -201,6 +364,12 @@ mod dao_factory {
         pub fn get_daos(&self) -> Vec<AccountId> {
             self.daos.to_vec()
         }
+
+        // (owner, pending owner)
+        #[ink(message)]
+        pub fn get_owner(&self) -> (AccountId, Option<AccountId>) {
+            (self.owner, self.pending_owner)
+        }
     }
 
     #[cfg(test)]
@@ -236,7 +405,7 @@ mod dao_factory {
             let mut dao_factory = init_dao_factory();
             let update_dao_code_hash: Result<(), DaoFactoryError> =
                 dao_factory.update_dao_code_hash(new_dao_code_hash);
-            assert_eq!(update_dao_code_hash.is_ok(), true);
+            assert!(update_dao_code_hash.is_ok());
 
         }
 
@@ -246,9 +415,48 @@ mod dao_factory {
             let mut dao_factory = init_dao_factory();
             let add_creator: Result<(), DaoFactoryError> =
                 dao_factory.add_whitelisted_creator(new_creator);
-            assert_eq!(add_creator.is_ok(), true);
+            assert!(add_creator.is_ok());
             
         }
+
+        #[ink::test]
+        fn test_upgrade_daos_only_owner() {
+            let mut dao_factory = init_dao_factory();
+            ink::env::test::set_caller::<Environment>(AccountId::from([0x09; 32]));
+            assert_eq!(dao_factory.upgrade_daos(0, 10), Err(DaoFactoryError::NotOwner));
+        }
+
+        #[ink::test]
+        fn test_transfer_ownership() {
+            let mut dao_factory = init_dao_factory();
+            let mock_accounts = get_mock_accounts();
+            let new_owner: AccountId = AccountId::from([0x05; 32]);
+
+            ink::env::test::set_caller::<Environment>(new_owner);
+            assert_eq!(dao_factory.transfer_ownership(new_owner), Err(DaoFactoryError::NotOwner));
+            assert_eq!(
+                dao_factory.transfer_dao_admin(AccountId::from([0x06; 32]), new_owner),
+                Err(DaoFactoryError::NotOwner)
+            );
+            assert_eq!(
+                dao_factory.transfer_dao_ownership(AccountId::from([0x06; 32]), new_owner),
+                Err(DaoFactoryError::NotOwner)
+            );
+
+            ink::env::test::set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao_factory.transfer_ownership(new_owner), Ok(()));
+            assert_eq!(dao_factory.get_owner(), (mock_accounts.0, Some(new_owner)));
+            assert_eq!(dao_factory.accept_ownership(), Err(DaoFactoryError::NotPendingOwner));
+
+            ink::env::test::set_caller::<Environment>(new_owner);
+            assert_eq!(dao_factory.accept_ownership(), Ok(()));
+            assert_eq!(dao_factory.get_owner(), (new_owner, None));
+            assert_eq!(dao_factory.change_open(false), Ok(()));
+
+            ink::env::test::set_caller::<Environment>(mock_accounts.0);
+            assert_eq!(dao_factory.change_open(true), Err(DaoFactoryError::NotOwner));
+            assert_eq!(ink::env::test::recorded_events().count(), 2);
+        }
     }
 
     #[cfg(all(test, feature = "e2e-tests"))]
@@ -256,6 +464,8 @@ mod dao_factory {
         /// Imports all the definitions from the outer scope so we can use them here.
         use super::*;
 
+        use dao::VotingMode;
+
         /// A helper function used for calling contract messages.
         use ink_e2e::build_message;
 
@@ -307,6 +517,9 @@ mod dao_factory {
                             use_default_settings: true,
                             quorum: 0,
                             threshold: 0,
+                            voting_mode: VotingMode::OneMemberOneVote,
+                            voting_token: AccountId::from([0x00; 32]),
+                            voting_window: 0,
                         },
                         Step {
                             step_index: 1,
@@ -314,6 +527,9 @@ mod dao_factory {
                             use_default_settings: true,
                             quorum: 0,
                             threshold: 0,
+                            voting_mode: VotingMode::OneMemberOneVote,
+                            voting_token: AccountId::from([0x00; 32]),
+                            voting_window: 0,
                         },
                     ],
                     vec![vec![step1_member], vec![step2_member]],
@@ -344,6 +560,28 @@ mod dao_factory {
 
             assert_eq!(dao_info.2, "Name".to_string());
 
+            // The DAO constructor rejects a name beyond the inline limit.
+            let create_invalid_dao = build_message::<DaoFactoryRef>(dao_factory_account_id.clone())
+                .call(|dao_factory| dao_factory.create_dao(
+                    2,
+                    "N".repeat(129),
+                    "Description".to_string(),
+                    "Website".to_string(),
+                    "Email".to_string(),
+                    "Address".to_string(),
+                    vec![],
+                    vec![],
+                    vec![],
+                    vec![],
+                    100,
+                    100,
+                    vec![],
+                    false,
+                    false,
+                ));
+            let create_invalid_dao_result = client.call_dry_run(&ink_e2e::alice(), &create_invalid_dao, 0, None).await;
+            assert_eq!(create_invalid_dao_result.return_value(), Err(DaoFactoryError::InvalidDaoParams(dao::Error::TitleTooLong)));
+
             // Get created DAOs
             let get_daos = build_message::<DaoFactoryRef>(dao_factory_account_id.clone())
             .call(|dao_factory| dao_factory.get_daos());
@@ -354,6 +592,132 @@ mod dao_factory {
             
             Ok(())
         }
+
+        #[ink_e2e::test(additional_contracts = "../dao/Cargo.toml")]
+        async fn test_upgrade_daos(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let owner = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let new_dao_owner = ink_e2e::account_id(ink_e2e::AccountKeyring::Charlie);
+            let oracle_address: AccountId = AccountId::from([0x02; 32]);
+            let step1_member = AccountId::from([0x05; 32]);
+
+            let dao_code_hash = client
+            .upload("dao", &ink_e2e::bob(), None)
+            .await
+            .expect("uploading `dao` failed")
+            .code_hash;
+            let dao_code_hash = dao_code_hash.as_ref().try_into().unwrap();
+
+            let constructor = DaoFactoryRef::new(owner, oracle_address, dao_code_hash);
+            let dao_factory_account_id = client
+                .instantiate("dao_factory", &ink_e2e::bob(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            // Create two DAOs.
+            let mut dao_account_ids: Vec<AccountId> = Vec::new();
+            for version in 1..=2u8 {
+                let create_dao = build_message::<DaoFactoryRef>(dao_factory_account_id.clone())
+                    .call(|dao_factory| dao_factory.create_dao(
+                        version,
+                        "Name".to_string(),
+                        "Description".to_string(),
+                        "Website".to_string(),
+                        "Email".to_string(),
+                        "Address".to_string(),
+                        vec!["twitter".to_string()],
+                        vec![
+                            Step {
+                                step_index: 0,
+                                title: "Step 1".to_string(),
+                                use_default_settings: true,
+                                quorum: 0,
+                                threshold: 0,
+                                voting_mode: VotingMode::OneMemberOneVote,
+                                voting_token: AccountId::from([0x00; 32]),
+                                voting_window: 0,
+                            },
+                        ],
+                        vec![vec![step1_member]],
+                        vec![],
+                        100,
+                        100,
+                        vec![owner],
+                        false,
+                        false,
+                    ));
+                let create_dao_result = client.call(&ink_e2e::alice(), create_dao, 0, None).await.expect("Create DAO failed");
+                dao_account_ids.push(create_dao_result.return_value().expect("DAO not created"));
+            }
+
+            // The first DAO has a program and a proposal to carry over.
+            let create_program = build_message::<DaoRef>(dao_account_ids[0].clone())
+                .call(|dao| dao.create_program("Program Title".to_string(), "Program Description".to_string(), 0, u64::MAX, None));
+            client.call(&ink_e2e::alice(), create_program, 0, None).await.expect("Create program failed");
+
+            let create_proposal = build_message::<DaoRef>(dao_account_ids[0].clone())
+                .call(|dao| dao.create_proposal(
+                    0,
+                    "Title".to_string(),
+                    "Description".to_string(),
+                    0,
+                    u64::MAX,
+                    false,
+                    0,
+                    "AZERO/USD".to_string(),
+                    100,
+                    AccountId::from([0x00; 32]),
+                    step1_member,
+                    true
+                ));
+            client.call(&ink_e2e::alice(), create_proposal, 0, None).await.expect("Create proposal failed");
+
+            // The second DAO is handed over to Charlie, so the factory can not upgrade it anymore.
+            let transfer_dao_ownership = build_message::<DaoFactoryRef>(dao_factory_account_id.clone())
+                .call(|dao_factory| dao_factory.transfer_dao_ownership(dao_account_ids[1], new_dao_owner));
+            client.call(&ink_e2e::alice(), transfer_dao_ownership, 0, None).await.expect("Transfer DAO ownership failed");
+
+            let accept_ownership = build_message::<DaoRef>(dao_account_ids[1].clone())
+                .call(|dao| dao.accept_ownership());
+            client.call(&ink_e2e::charlie(), accept_ownership, 0, None).await.expect("Accept ownership failed");
+
+            // The batch rolls the first DAO forward and reports the second one.
+            let upgrade_daos = build_message::<DaoFactoryRef>(dao_factory_account_id.clone())
+                .call(|dao_factory| dao_factory.upgrade_daos(0, 10));
+            let upgrade_daos_result = client.call(&ink_e2e::alice(), upgrade_daos, 0, None).await.expect("Upgrade DAOs failed");
+            assert_eq!(upgrade_daos_result.return_value(), Ok(vec![(dao_account_ids[1], DaoUpgradeFailure::NotUpgraded)]));
+
+            // The upgraded DAO has migrated its storage to the current version and kept its content.
+            let get_storage_version = build_message::<DaoRef>(dao_account_ids[0].clone())
+                .call(|dao| dao.get_storage_version());
+            let get_storage_version_result = client.call_dry_run(&ink_e2e::alice(), &get_storage_version, 0, None).await;
+            assert_eq!(get_storage_version_result.return_value(), dao::STORAGE_VERSION);
+
+            let get_program = build_message::<DaoRef>(dao_account_ids[0].clone())
+                .call(|dao| dao.get_program(0));
+            let get_program_result = client.call_dry_run(&ink_e2e::alice(), &get_program, 0, None).await;
+            assert_eq!(get_program_result.return_value().expect("Program not found").title, "Program Title".to_string());
+
+            let get_proposal = build_message::<DaoRef>(dao_account_ids[0].clone())
+                .call(|dao| dao.get_proposal(0));
+            let get_proposal_result = client.call_dry_run(&ink_e2e::alice(), &get_proposal, 0, None).await;
+            let proposal = get_proposal_result.return_value().expect("Proposal not found");
+            assert_eq!((proposal.payment_amount_crypto, proposal.to), (100, step1_member));
+
+            // Rolling forward again keeps the storage and still reports the foreign DAO.
+            let upgrade_daos = build_message::<DaoFactoryRef>(dao_factory_account_id.clone())
+                .call(|dao_factory| dao_factory.upgrade_daos(0, 10));
+            let upgrade_daos_result = client.call(&ink_e2e::alice(), upgrade_daos, 0, None).await.expect("Upgrade DAOs failed");
+            assert_eq!(upgrade_daos_result.return_value(), Ok(vec![(dao_account_ids[1], DaoUpgradeFailure::NotUpgraded)]));
+
+            // The skipped DAO still belongs to Charlie.
+            let get_dao_info = build_message::<DaoRef>(dao_account_ids[1].clone())
+                .call(|dao| dao.get_info());
+            let get_dao_info_result = client.call_dry_run(&ink_e2e::alice(), &get_dao_info, 0, None).await;
+            assert_eq!(get_dao_info_result.return_value().0, new_dao_owner);
+
+            Ok(())
+        }
         
     }
 }
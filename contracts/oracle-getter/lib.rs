@@ -9,9 +9,27 @@ pub trait OracleGetters {
     #[ink(message)]
     fn get_updater(&self) -> AccountId;
 
+    // All authorized updaters, get_updater returns the first one.
+    #[ink(message)]
+    fn get_updaters(&self) -> Vec<AccountId>;
+
+    // (min submissions, max submission age)
+    #[ink(message)]
+    fn get_aggregation(&self) -> (u32, u64);
+
     #[ink(message)]
     fn get_latest_price(&self, pair: String) -> Option<(u64, u128)>;
 
     #[ink(message)]
     fn get_latest_prices(&self, pairs: Vec<String>) -> Vec<Option<(u64, u128)>>;
+
+    // The (timestamp, price) of a pair in effect at a timestamp,
+    // None if it is older than the kept price history.
+    #[ink(message)]
+    fn get_price_at(&self, pair: String, timestamp: u64) -> Option<(u64, u128)>;
+
+    // Time-weighted average price of a pair over the last `window` milliseconds,
+    // limited to the kept price history. A zero window returns the latest price.
+    #[ink(message)]
+    fn get_twap(&self, pair: String, window: u64) -> Option<u128>;
 }
\ No newline at end of file
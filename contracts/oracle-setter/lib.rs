@@ -4,17 +4,49 @@ use ink::prelude::string::String;
 use ink::prelude::vec::Vec;
 use ink::primitives::AccountId;
 
+// Errors returned by the oracle setters.
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum OracleError {
+    NotInitialized,
+    NotOwner,
+    NotPendingOwner,
+    NotUpdater,
+    UpdaterExisted,
+    UpdaterNotFound,
+    InvalidMinSubmissions,
+    NotEnoughUpdaters,
+    SetCodeFailed,
+    MultipleUpdaters,
+}
+
 #[ink::trait_definition]
 pub trait OracleSetters {
+    // Start transferring the ownership, the new owner has to accept it.
+    #[ink(message)]
+    fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), OracleError>;
+
+    #[ink(message)]
+    fn accept_ownership(&mut self) -> Result<(), OracleError>;
+
+    // Replace the updater, fails with MultipleUpdaters if there are several updaters.
+    #[ink(message)]
+    fn set_updater(&mut self, updater: AccountId) -> Result<(), OracleError>;
+
+    #[ink(message)]
+    fn add_updater(&mut self, updater: AccountId) -> Result<(), OracleError>;
+
     #[ink(message)]
-    fn transfer_ownership(&mut self, new_owner: AccountId);
+    fn remove_updater(&mut self, updater: AccountId) -> Result<(), OracleError>;
 
+    // A price is aggregated from at least `min_submissions` updater submissions
+    // not older than `max_submission_age` milliseconds, 0 means no age limit.
     #[ink(message)]
-    fn set_updater(&mut self, updater: AccountId);
+    fn set_aggregation(&mut self, min_submissions: u32, max_submission_age: u64) -> Result<(), OracleError>;
 
     #[ink(message)]
-    fn set_price(&mut self, pair: String, price: u128);
+    fn set_price(&mut self, pair: String, price: u128) -> Result<(), OracleError>;
 
     #[ink(message)]
-    fn set_prices(&mut self, pairs: Vec<(String, u128)>);
+    fn set_prices(&mut self, pairs: Vec<(String, u128)>) -> Result<(), OracleError>;
 }
@@ -1,4 +1,6 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
+// Price records are (timestamp, price) tuples keyed by pair.
+#![allow(clippy::type_complexity)]
 
 pub use self::oracle_anchor::TokenPriceStorageRef;
 
@@ -9,13 +11,120 @@ pub mod oracle_anchor {
     use ink::storage::{traits::ManualKey, Lazy, Mapping};
 
     use dia_oracle_getter::OracleGetters;
-    use dia_oracle_setter::OracleSetters;
+    use dia_oracle_setter::{OracleError, OracleSetters};
+
+    // Number of historical prices kept per pair.
+    pub const PRICE_HISTORY_SIZE: u32 = 64;
 
     #[ink::storage_item]
     struct TokenPriceStruct {
         owner: AccountId,
         updater: AccountId,
         pairs: Mapping<String, (u64, u128)>,
+        // Ring buffer of the last PRICE_HISTORY_SIZE (timestamp, price) records of each pair.
+        // Key: (pair, slot)
+        history: Mapping<(String, u32), (u64, u128)>,
+        // Key: pair, value: (next slot, number of records)
+        history_cursor: Mapping<String, (u32, u32)>,
+        // Authorized updaters, empty means only `updater`.
+        updaters: Lazy<Vec<AccountId>>,
+        // Minimum number of fresh submissions to aggregate a price, 0 means 1.
+        min_submissions: Lazy<u32>,
+        // Maximum age of a submission in milliseconds, 0 means no limit.
+        max_submission_age: Lazy<u64>,
+        // Latest submission of each updater.
+        // Key: (pair, updater), value: (timestamp, price)
+        submissions: Mapping<(String, AccountId), (u64, u128)>,
+        // Account that can accept the ownership.
+        pending_owner: Lazy<Option<AccountId>>,
+    }
+
+    impl TokenPriceStruct {
+        // Store the latest price of a pair and append it to the price history.
+        // Prices set at the same timestamp replace each other.
+        fn record_price(&mut self, pair: &String, timestamp: u64, price: u128) {
+            self.pairs.insert(pair, &(timestamp, price));
+
+            let (mut next_slot, mut count) = self.history_cursor.get(pair).unwrap_or_default();
+            let last_slot: u32 = (next_slot + PRICE_HISTORY_SIZE - 1) % PRICE_HISTORY_SIZE;
+            let same_timestamp: bool = count > 0
+                && self
+                    .history
+                    .get((pair.clone(), last_slot))
+                    .is_some_and(|(last_timestamp, _)| last_timestamp == timestamp);
+            if same_timestamp {
+                self.history.insert((pair.clone(), last_slot), &(timestamp, price));
+                return;
+            }
+
+            self.history.insert((pair.clone(), next_slot), &(timestamp, price));
+            next_slot = (next_slot + 1) % PRICE_HISTORY_SIZE;
+            count = (count + 1).min(PRICE_HISTORY_SIZE);
+            self.history_cursor.insert(pair, &(next_slot, count));
+        }
+
+        fn get_updaters(&self) -> Vec<AccountId> {
+            let updaters: Vec<AccountId> = self.updaters.get().unwrap_or_default();
+            if updaters.is_empty() {
+                return ink::prelude::vec![self.updater];
+            }
+            updaters
+        }
+
+        fn get_min_submissions(&self) -> u32 {
+            self.min_submissions.get().unwrap_or_default().max(1)
+        }
+
+        // (timestamp, median) of the fresh submissions of the current updaters for a pair,
+        // None if there are fewer than the minimum number of submissions.
+        // The timestamp is the one of the oldest submission included, not before the latest price of the pair.
+        // The median of an even number of submissions is the mean of the middle two, rounded down.
+        fn aggregate_price(&self, pair: &str, now: u64) -> Option<(u64, u128)> {
+            let max_submission_age: u64 = self.max_submission_age.get().unwrap_or_default();
+            let mut prices: Vec<u128> = Vec::new();
+            let mut oldest_timestamp: u64 = now;
+            for updater in self.get_updaters() {
+                if let Some((timestamp, price)) = self.submissions.get((String::from(pair), updater)) {
+                    if max_submission_age == 0 || now.saturating_sub(timestamp) <= max_submission_age {
+                        prices.push(price);
+                        oldest_timestamp = oldest_timestamp.min(timestamp);
+                    }
+                }
+            }
+            if prices.is_empty() || (prices.len() as u32) < self.get_min_submissions() {
+                return None;
+            }
+            // Keep the price history ordered when an older submission is included again.
+            if let Some((latest_timestamp, _)) = self.pairs.get(String::from(pair)) {
+                oldest_timestamp = oldest_timestamp.max(latest_timestamp);
+            }
+
+            prices.sort_unstable();
+            let middle: usize = prices.len() / 2;
+            if prices.len() % 2 == 1 {
+                return Some((oldest_timestamp, prices[middle]));
+            }
+            let (low, high) = (prices[middle - 1], prices[middle]);
+            Some((oldest_timestamp, low / 2 + high / 2 + (low % 2 + high % 2) / 2))
+        }
+
+        // Price history of a pair, newest first.
+        // Pairs set before the history was kept only have their latest price.
+        fn price_history(&self, pair: &String) -> Vec<(u64, u128)> {
+            let (next_slot, count) = self.history_cursor.get(pair).unwrap_or_default();
+            if count == 0 {
+                return self.pairs.get(pair).into_iter().collect();
+            }
+
+            let mut prices: Vec<(u64, u128)> = Vec::new();
+            for i in 1..=count {
+                let slot: u32 = (next_slot + PRICE_HISTORY_SIZE - i) % PRICE_HISTORY_SIZE;
+                if let Some(price) = self.history.get((pair.clone(), slot)) {
+                    prices.push(price);
+                }
+            }
+            prices
+        }
     }
 
     #[ink(storage)]
@@ -31,6 +140,14 @@ pub mod oracle_anchor {
         new_owner: AccountId,
     }
 
+    #[ink(event)]
+    pub struct OwnershipTransferStarted {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
     #[ink(event)]
     pub struct UpdaterChanged {
         #[ink(topic)]
@@ -39,6 +156,7 @@ pub mod oracle_anchor {
         new: AccountId,
     }
 
+    // Emitted when an aggregated price is finalized.
     #[ink(event)]
     pub struct TokenPriceChanged {
         #[ink(topic)]
@@ -47,75 +165,190 @@ pub mod oracle_anchor {
         timestamp: u64,
     }
 
+    #[ink(event)]
+    pub struct PriceSubmitted {
+        #[ink(topic)]
+        pair: String,
+        #[ink(topic)]
+        updater: AccountId,
+        price: u128,
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct UpdaterAdded {
+        #[ink(topic)]
+        updater: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct UpdaterRemoved {
+        #[ink(topic)]
+        updater: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AggregationChanged {
+        min_submissions: u32,
+        max_submission_age: u64,
+    }
+
     impl OracleSetters for TokenPriceStorage {
         #[ink(message)]
-        fn transfer_ownership(&mut self, new_owner: AccountId) {
+        fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), OracleError> {
+            let caller: AccountId = self.env().caller();
+
+            let mut tps: TokenPriceStruct = self._get_data()?;
+
+            if caller != tps.owner {
+                return Err(OracleError::NotOwner);
+            }
+            tps.pending_owner.set(&Some(new_owner));
+            self.env().emit_event(OwnershipTransferStarted {
+                previous_owner: caller,
+                new_owner,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn accept_ownership(&mut self) -> Result<(), OracleError> {
             let caller: AccountId = self.env().caller();
 
-            let mut tps: TokenPriceStruct = self.data.get().expect("self.data not set");
+            let mut tps: TokenPriceStruct = self._get_data()?;
 
-            assert!(caller == tps.owner, "only owner can transfer ownership");
-            tps.owner = new_owner;
+            if tps.pending_owner.get().flatten() != Some(caller) {
+                return Err(OracleError::NotPendingOwner);
+            }
+            let previous_owner: AccountId = tps.owner;
+            tps.owner = caller;
+            tps.pending_owner.set(&None);
             self.data.set(&tps);
             self.env().emit_event(OwnershipTransferred {
-                previous_owner: Some(caller),
-                new_owner,
+                previous_owner: Some(previous_owner),
+                new_owner: caller,
             });
+            Ok(())
         }
 
         #[ink(message)]
-        fn set_updater(&mut self, updater: AccountId) {
+        fn set_updater(&mut self, updater: AccountId) -> Result<(), OracleError> {
             let caller: AccountId = self.env().caller();
 
-            let mut tps: TokenPriceStruct = self.data.get().expect("self.data not set");
+            let mut tps: TokenPriceStruct = self._get_data()?;
 
-            assert!(caller == tps.owner, "only owner can set updater");
+            if caller != tps.owner {
+                return Err(OracleError::NotOwner);
+            }
+            // Several updaters are changed with add_updater and remove_updater.
+            if tps.get_updaters().len() > 1 {
+                return Err(OracleError::MultipleUpdaters);
+            }
             tps.updater = updater;
+            tps.updaters.set(&ink::prelude::vec![updater]);
             self.data.set(&tps);
             self.env().emit_event(UpdaterChanged {
                 old: Some(caller),
                 new: updater,
             });
+            Ok(())
         }
 
         #[ink(message)]
-        fn set_price(&mut self, pair: String, price: u128) {
+        fn add_updater(&mut self, updater: AccountId) -> Result<(), OracleError> {
             let caller: AccountId = self.env().caller();
-            let mut tps: TokenPriceStruct = self.data.get().expect("self.data not set");
-            assert!(caller == tps.updater, "only updater can set price");
-            let current_timestamp: u64 = self.env().block_timestamp();
+            let mut tps: TokenPriceStruct = self._get_data()?;
+            if caller != tps.owner {
+                return Err(OracleError::NotOwner);
+            }
 
-            // create new record
+            let mut updaters: Vec<AccountId> = tps.get_updaters();
+            if updaters.contains(&updater) {
+                return Err(OracleError::UpdaterExisted);
+            }
+            updaters.push(updater);
+            tps.updaters.set(&updaters);
+            self.env().emit_event(UpdaterAdded { updater });
+            Ok(())
+        }
 
-            tps.pairs.insert(pair.clone(), &(current_timestamp, price));
+        #[ink(message)]
+        fn remove_updater(&mut self, updater: AccountId) -> Result<(), OracleError> {
+            let caller: AccountId = self.env().caller();
+            let mut tps: TokenPriceStruct = self._get_data()?;
+            if caller != tps.owner {
+                return Err(OracleError::NotOwner);
+            }
 
+            let mut updaters: Vec<AccountId> = tps.get_updaters();
+            let index: usize = updaters
+                .iter()
+                .position(|account| *account == updater)
+                .ok_or(OracleError::UpdaterNotFound)?;
+            updaters.remove(index);
+            if (updaters.len() as u32) < tps.get_min_submissions() {
+                return Err(OracleError::NotEnoughUpdaters);
+            }
+            tps.updater = updaters[0];
+            tps.updaters.set(&updaters);
             self.data.set(&tps);
+            self.env().emit_event(UpdaterRemoved { updater });
+            Ok(())
+        }
 
-            self.env().emit_event(TokenPriceChanged {
-                pair,
-                price,
-                timestamp: current_timestamp,
+        #[ink(message)]
+        fn set_aggregation(&mut self, min_submissions: u32, max_submission_age: u64) -> Result<(), OracleError> {
+            let caller: AccountId = self.env().caller();
+            let mut tps: TokenPriceStruct = self._get_data()?;
+            if caller != tps.owner {
+                return Err(OracleError::NotOwner);
+            }
+            if min_submissions == 0 || min_submissions > tps.get_updaters().len() as u32 {
+                return Err(OracleError::InvalidMinSubmissions);
+            }
+
+            tps.min_submissions.set(&min_submissions);
+            tps.max_submission_age.set(&max_submission_age);
+            self.env().emit_event(AggregationChanged {
+                min_submissions,
+                max_submission_age,
             });
+            Ok(())
         }
 
         #[ink(message)]
-        fn set_prices(&mut self, pairs: Vec<(String, u128)>) {
+        fn set_price(&mut self, pair: String, price: u128) -> Result<(), OracleError> {
             let caller: AccountId = self.env().caller();
-            let mut tps: TokenPriceStruct = self.data.get().expect("self.data not set");
-            assert!(caller == tps.updater, "only updater can set price");
+            let mut tps: TokenPriceStruct = self._get_data()?;
+            if !tps.get_updaters().contains(&caller) {
+                return Err(OracleError::NotUpdater);
+            }
+            let current_timestamp: u64 = self.env().block_timestamp();
+
+            // create new record
+
+            self._submit_price(&mut tps, caller, pair, price, current_timestamp);
+
+            self.data.set(&tps);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn set_prices(&mut self, pairs: Vec<(String, u128)>) -> Result<(), OracleError> {
+            let caller: AccountId = self.env().caller();
+            let mut tps: TokenPriceStruct = self._get_data()?;
+            if !tps.get_updaters().contains(&caller) {
+                return Err(OracleError::NotUpdater);
+            }
             let current_timestamp: u64 = self.env().block_timestamp();
 
             // create new record
             for (pair, price) in pairs {
-                tps.pairs.insert(pair.clone(), &(current_timestamp, price));
-                self.env().emit_event(TokenPriceChanged {
-                    pair,
-                    price,
-                    timestamp: current_timestamp,
-                });
+                self._submit_price(&mut tps, caller, pair, price, current_timestamp);
             }
 
             self.data.set(&tps);
+            Ok(())
         }
     }
 
@@ -125,6 +358,20 @@ pub mod oracle_anchor {
             self.data.get().unwrap().updater
         }
 
+        #[ink(message)]
+        fn get_updaters(&self) -> Vec<AccountId> {
+            self.data.get().unwrap().get_updaters()
+        }
+
+        #[ink(message)]
+        fn get_aggregation(&self) -> (u32, u64) {
+            let data = self.data.get().unwrap();
+            (
+                data.get_min_submissions(),
+                data.max_submission_age.get().unwrap_or_default(),
+            )
+        }
+
         #[ink(message)]
         fn get_latest_price(&self, pair: String) -> Option<(u64, u128)> {
             self.data.get().unwrap().pairs.get(pair)
@@ -139,6 +386,48 @@ pub mod oracle_anchor {
             }
             result
         }
+
+        #[ink(message)]
+        fn get_price_at(&self, pair: String, timestamp: u64) -> Option<(u64, u128)> {
+            self.data
+                .get()
+                .unwrap()
+                .price_history(&pair)
+                .into_iter()
+                .find(|(price_timestamp, _)| *price_timestamp <= timestamp)
+        }
+
+        #[ink(message)]
+        fn get_twap(&self, pair: String, window: u64) -> Option<u128> {
+            let prices: Vec<(u64, u128)> = self.data.get().unwrap().price_history(&pair);
+            let (_, latest_price) = *prices.first()?;
+            if window == 0 {
+                return Some(latest_price);
+            }
+
+            // Each price is in effect from its timestamp until the next price.
+            let now: u64 = self.env().block_timestamp();
+            let window_start: u64 = now.saturating_sub(window);
+            let mut period_end: u64 = now;
+            let mut weighted_sum: u128 = 0;
+            let mut total_duration: u64 = 0;
+            for (price_timestamp, price) in prices {
+                let period_start: u64 = price_timestamp.max(window_start);
+                let duration: u64 = period_end.saturating_sub(period_start);
+                weighted_sum = weighted_sum.checked_add(price.checked_mul(duration as u128)?)?;
+                total_duration += duration;
+                if price_timestamp <= window_start {
+                    break;
+                }
+                period_end = period_start;
+            }
+
+            // All prices in the window were set at the current timestamp.
+            if total_duration == 0 {
+                return Some(latest_price);
+            }
+            Some(weighted_sum / total_duration as u128)
+        }
     }
 
     impl Default for TokenPriceStorage {
@@ -164,6 +453,13 @@ pub mod oracle_anchor {
                 owner: caller,
                 updater: caller,
                 pairs: Mapping::new(),
+                history: Mapping::new(),
+                history_cursor: Mapping::new(),
+                updaters: Lazy::new(),
+                min_submissions: Lazy::new(),
+                max_submission_age: Lazy::new(),
+                submissions: Mapping::new(),
+                pending_owner: Lazy::new(),
             };
 
             let mut ldata = Lazy::new();
@@ -172,21 +468,61 @@ pub mod oracle_anchor {
             Self { data: ldata }
         }
 
+        // Store the submission of an updater and finalize the aggregated price
+        // of the pair once there are enough fresh submissions.
+        fn _submit_price(
+            &self,
+            tps: &mut TokenPriceStruct,
+            updater: AccountId,
+            pair: String,
+            price: u128,
+            timestamp: u64,
+        ) {
+            tps.submissions.insert((pair.clone(), updater), &(timestamp, price));
+            self.env().emit_event(PriceSubmitted {
+                pair: pair.clone(),
+                updater,
+                price,
+                timestamp,
+            });
+
+            if let Some((aggregated_timestamp, aggregated_price)) = tps.aggregate_price(&pair, timestamp) {
+                tps.record_price(&pair, aggregated_timestamp, aggregated_price);
+                self.env().emit_event(TokenPriceChanged {
+                    pair,
+                    price: aggregated_price,
+                    timestamp: aggregated_timestamp,
+                });
+            }
+        }
+
+        // (owner, pending owner)
+        #[ink(message)]
+        pub fn get_owner(&self) -> (AccountId, Option<AccountId>) {
+            let data = self.data.get().unwrap();
+            (data.owner, data.pending_owner.get().flatten())
+        }
+
         #[ink(message)]
         pub fn code_hash(&self) -> Hash {
-            self.env().own_code_hash().unwrap_or(Default::default())
+            self.env().own_code_hash().unwrap_or_default()
         }
 
         #[ink(message)]
-        pub fn set_code(&mut self, code_hash: [u8; 32]) {
+        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<(), OracleError> {
             let caller: AccountId = self.env().caller();
-            let tps: TokenPriceStruct = self.data.get().expect("self.data not set");
-            assert!(caller == tps.owner, "only owner can set set code");
+            let tps: TokenPriceStruct = self._get_data()?;
+            if caller != tps.owner {
+                return Err(OracleError::NotOwner);
+            }
 
-            ink::env::set_code_hash(&code_hash).unwrap_or_else(|err| {
-                panic!("Failed to `set_code_hash` to {code_hash:?} due to {err:?}")
-            });
+            ink::env::set_code_hash(&code_hash).map_err(|_| OracleError::SetCodeFailed)?;
             ink::env::debug_println!("Switched code hash to {:?}.", code_hash);
+            Ok(())
+        }
+
+        fn _get_data(&self) -> Result<TokenPriceStruct, OracleError> {
+            self.data.get().ok_or(OracleError::NotInitialized)
         }
     }
     #[cfg(test)]
@@ -433,19 +769,31 @@ pub mod oracle_anchor {
         #[ink::test]
         fn transfer_ownership_works() {
             let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
-            token_price_storage.transfer_ownership(AccountId::from([0x02; 32]));
+            assert_eq!(token_price_storage.transfer_ownership(AccountId::from([0x02; 32])), Ok(()));
+            // The owner changes once the new owner accepts.
+            assert_eq!(
+                token_price_storage.get_owner(),
+                (AccountId::from([0x01; 32]), Some(AccountId::from([0x02; 32])))
+            );
+            assert_eq!(
+                token_price_storage.accept_ownership(),
+                Err(OracleError::NotPendingOwner)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(AccountId::from([0x02; 32]));
+            assert_eq!(token_price_storage.accept_ownership(), Ok(()));
             assert_eq!(
                 token_price_storage.data.get().unwrap().owner,
                 AccountId::from([0x02; 32]),
                 "transfer ownership failed"
             );
+            assert_eq!(token_price_storage.get_owner(), (AccountId::from([0x02; 32]), None));
 
             let emitted_events: Vec<ink::env::test::EmittedEvent> =
                 ink::env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 3);
+            assert_eq!(emitted_events.len(), 4);
             assert_transfer_ownership_event(&emitted_events[0], None, AccountId::from([0x01; 32]));
             assert_transfer_ownership_event(
-                &emitted_events[2],
+                &emitted_events[3],
                 Some(AccountId::from([0x01; 32])),
                 AccountId::from([0x02; 32]),
             );
@@ -454,7 +802,7 @@ pub mod oracle_anchor {
         #[ink::test]
         fn set_updater_works() {
             let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
-            token_price_storage.set_updater(AccountId::from([0x02; 32]));
+            assert_eq!(token_price_storage.set_updater(AccountId::from([0x02; 32])), Ok(()));
             assert_eq!(
                 token_price_storage.get_updater(),
                 AccountId::from([0x02; 32])
@@ -476,14 +824,15 @@ pub mod oracle_anchor {
             let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
 
             const PRICE: u128 = 1001;
-            token_price_storage.set_price("abc".to_string(), PRICE);
+            assert_eq!(token_price_storage.set_price("abc".to_string(), PRICE), Ok(()));
             assert_eq!(
                 token_price_storage.get_latest_price("abc".to_string()),
                 Some((0, PRICE))
             );
 
+            // The aggregated price follows the PriceSubmitted event.
             assert_token_price_changed_event(
-                &ink::env::test::recorded_events().collect::<Vec<_>>()[2],
+                &ink::env::test::recorded_events().collect::<Vec<_>>()[3],
                 "abc".to_string(),
                 PRICE,
                 0,
@@ -496,12 +845,12 @@ pub mod oracle_anchor {
 
             const PRICE: u128 = 1001;
 
-            token_price_storage.set_price("abc".to_string(), PRICE);
+            assert_eq!(token_price_storage.set_price("abc".to_string(), PRICE), Ok(()));
 
             let mut latest_price = token_price_storage.get_latest_price("abc".to_string());
             assert_eq!(latest_price, Some((0, PRICE)));
 
-            token_price_storage.set_price("abc".to_string(), PRICE + 10);
+            assert_eq!(token_price_storage.set_price("abc".to_string(), PRICE + 10), Ok(()));
 
             latest_price = token_price_storage.get_latest_price("abc".to_string());
 
@@ -509,23 +858,252 @@ pub mod oracle_anchor {
         }
 
         #[ink::test]
-        #[should_panic]
-        fn set_price_panic() {
+        fn price_history_works() {
+            let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
+            let pair: String = "abc".to_string();
+            assert_eq!(token_price_storage.get_price_at(pair.clone(), 0), None);
+
+            for i in 1..=3 {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(i * 1000);
+                assert_eq!(token_price_storage.set_price(pair.clone(), i as u128 * 100), Ok(()));
+            }
+            // A second price in the same block replaces the first one.
+            assert_eq!(token_price_storage.set_price(pair.clone(), 350), Ok(()));
+
+            assert_eq!(token_price_storage.get_price_at(pair.clone(), 999), None);
+            assert_eq!(token_price_storage.get_price_at(pair.clone(), 1000), Some((1000, 100)));
+            assert_eq!(token_price_storage.get_price_at(pair.clone(), 2500), Some((2000, 200)));
+            assert_eq!(token_price_storage.get_price_at(pair.clone(), 5000), Some((3000, 350)));
+            assert_eq!(token_price_storage.get_latest_price(pair.clone()), Some((3000, 350)));
+            assert_eq!(token_price_storage.get_price_at("xyz".to_string(), 5000), None);
+
+            // Only the last PRICE_HISTORY_SIZE prices are kept.
+            for i in 4..=(PRICE_HISTORY_SIZE as u64 + 3) {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(i * 1000);
+                assert_eq!(token_price_storage.set_price(pair.clone(), i as u128 * 100), Ok(()));
+            }
+            assert_eq!(token_price_storage.get_price_at(pair.clone(), 3000), None);
+            assert_eq!(token_price_storage.get_price_at(pair.clone(), 4000), Some((4000, 400)));
+        }
+
+        #[ink::test]
+        fn twap_works() {
+            let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
+            let pair: String = "abc".to_string();
+            assert_eq!(token_price_storage.get_twap(pair.clone(), 1000), None);
+
+            // 100 from 1000 to 2000, 400 from 2000 to 2500, 1000 from 2500.
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1000);
+            assert_eq!(token_price_storage.set_price(pair.clone(), 100), Ok(()));
+            assert_eq!(token_price_storage.get_twap(pair.clone(), 1000), Some(100));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2000);
+            assert_eq!(token_price_storage.set_price(pair.clone(), 400), Ok(()));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2500);
+            assert_eq!(token_price_storage.set_price(pair.clone(), 1000), Ok(()));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(3000);
+            assert_eq!(token_price_storage.get_twap(pair.clone(), 0), Some(1000));
+            // (1000 * 500) / 500
+            assert_eq!(token_price_storage.get_twap(pair.clone(), 500), Some(1000));
+            // (1000 * 500 + 400 * 500) / 1000
+            assert_eq!(token_price_storage.get_twap(pair.clone(), 1000), Some(700));
+            // (1000 * 500 + 400 * 500 + 100 * 1000) / 2000
+            assert_eq!(token_price_storage.get_twap(pair.clone(), 2000), Some(400));
+            // The window is limited to the price history starting at 1000.
+            assert_eq!(token_price_storage.get_twap(pair.clone(), 10_000), Some(400));
+        }
+
+        #[ink::test]
+        fn median_of_updaters_works() {
+            let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let pair: String = "abc".to_string();
+
+            assert_eq!(token_price_storage.add_updater(accounts.bob), Ok(()));
+            assert_eq!(token_price_storage.add_updater(accounts.charlie), Ok(()));
+            assert_eq!(token_price_storage.set_aggregation(2, 1000), Ok(()));
+            assert_eq!(
+                token_price_storage.get_updaters(),
+                vec![accounts.alice, accounts.bob, accounts.charlie]
+            );
+            assert_eq!(token_price_storage.get_aggregation(), (2, 1000));
+
+            let submit = |token_price_storage: &mut TokenPriceStorage, updater: AccountId, price: u128| {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(updater);
+                assert_eq!(token_price_storage.set_price("abc".to_string(), price), Ok(()));
+            };
+
+            // One submission is not enough.
+            submit(&mut token_price_storage, accounts.alice, 100);
+            assert_eq!(token_price_storage.get_latest_price(pair.clone()), None);
+            // Median of 100 and 300
+            submit(&mut token_price_storage, accounts.bob, 300);
+            assert_eq!(token_price_storage.get_latest_price(pair.clone()), Some((0, 200)));
+            // Median of 100, 300 and 250
+            submit(&mut token_price_storage, accounts.charlie, 250);
+            assert_eq!(token_price_storage.get_latest_price(pair.clone()), Some((0, 250)));
+
+            // Submissions of alice and bob are stale.
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(5000);
+            submit(&mut token_price_storage, accounts.charlie, 400);
+            assert_eq!(token_price_storage.get_latest_price(pair.clone()), Some((0, 250)));
+            submit(&mut token_price_storage, accounts.bob, 501);
+            assert_eq!(token_price_storage.get_latest_price(pair.clone()), Some((5000, 450)));
+
+            let emitted_events = ink::env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_events: Vec<Event> = emitted_events
+                .iter()
+                .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect();
+            let submitted: usize = decoded_events
+                .iter()
+                .filter(|event| matches!(event, Event::PriceSubmitted(_)))
+                .count();
+            let aggregated: usize = decoded_events
+                .iter()
+                .filter(|event| matches!(event, Event::TokenPriceChanged(_)))
+                .count();
+            assert_eq!((submitted, aggregated), (5, 3));
+
+            // Removing the first updater makes the next one the primary updater.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(token_price_storage.remove_updater(accounts.alice), Ok(()));
+            assert_eq!(token_price_storage.get_updaters(), vec![accounts.bob, accounts.charlie]);
+            assert_eq!(token_price_storage.get_updater(), accounts.bob);
+        }
+
+        #[ink::test]
+        fn set_aggregation_fails() {
+            let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
+            // Only one updater
+            assert_eq!(
+                token_price_storage.set_aggregation(2, 0),
+                Err(OracleError::InvalidMinSubmissions)
+            );
+            assert_eq!(
+                token_price_storage.set_aggregation(0, 0),
+                Err(OracleError::InvalidMinSubmissions)
+            );
+            assert_eq!(
+                token_price_storage.remove_updater(AccountId::from([0x01; 32])),
+                Err(OracleError::NotEnoughUpdaters)
+            );
+            assert_eq!(
+                token_price_storage.remove_updater(AccountId::from([0x02; 32])),
+                Err(OracleError::UpdaterNotFound)
+            );
+            assert_eq!(
+                token_price_storage.add_updater(AccountId::from([0x01; 32])),
+                Err(OracleError::UpdaterExisted)
+            );
+        }
+
+        #[ink::test]
+        fn set_updater_fails() {
+            let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(token_price_storage.add_updater(accounts.bob), Ok(()));
+            assert_eq!(token_price_storage.set_aggregation(2, 1000), Ok(()));
+
+            assert_eq!(
+                token_price_storage.set_updater(accounts.charlie),
+                Err(OracleError::MultipleUpdaters)
+            );
+            // The updaters and the aggregation are kept.
+            assert_eq!(token_price_storage.get_updaters(), vec![accounts.alice, accounts.bob]);
+            assert_eq!(token_price_storage.get_aggregation(), (2, 1000));
+
+            // With a single updater left, it can be replaced.
+            assert_eq!(token_price_storage.set_aggregation(1, 1000), Ok(()));
+            assert_eq!(token_price_storage.remove_updater(accounts.bob), Ok(()));
+            assert_eq!(token_price_storage.set_updater(accounts.charlie), Ok(()));
+            assert_eq!(token_price_storage.get_updaters(), vec![accounts.charlie]);
+            assert_eq!(token_price_storage.get_aggregation(), (1, 1000));
+        }
+
+        #[ink::test]
+        fn aggregated_price_timestamp_works() {
+            let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let pair: String = "abc".to_string();
+            assert_eq!(token_price_storage.add_updater(accounts.bob), Ok(()));
+            // No age limit
+            assert_eq!(token_price_storage.set_aggregation(2, 0), Ok(()));
+
+            let submit = |token_price_storage: &mut TokenPriceStorage, updater: AccountId, timestamp: u64, price: u128| {
+                ink::env::test::set_caller::<ink::env::DefaultEnvironment>(updater);
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(timestamp);
+                assert_eq!(token_price_storage.set_price("abc".to_string(), price), Ok(()));
+            };
+
+            // The price is as old as the submission of alice.
+            submit(&mut token_price_storage, accounts.alice, 1000, 100);
+            submit(&mut token_price_storage, accounts.bob, 2000, 300);
+            assert_eq!(token_price_storage.get_latest_price(pair.clone()), Some((1000, 200)));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(token_price_storage.add_updater(accounts.charlie), Ok(()));
+            assert_eq!(token_price_storage.remove_updater(accounts.bob), Ok(()));
+            submit(&mut token_price_storage, accounts.alice, 10_000, 500);
+            submit(&mut token_price_storage, accounts.charlie, 11_000, 700);
+            assert_eq!(token_price_storage.get_latest_price(pair.clone()), Some((10_000, 600)));
+
+            // bob added again brings back its submission at 2000,
+            // the price is not older than the latest one.
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(token_price_storage.add_updater(accounts.bob), Ok(()));
+            submit(&mut token_price_storage, accounts.alice, 12_000, 900);
+            assert_eq!(token_price_storage.get_latest_price(pair.clone()), Some((10_000, 700)));
+            assert_eq!(token_price_storage.get_price_at(pair.clone(), 9999), Some((1000, 200)));
+        }
+
+        #[ink::test]
+        fn set_price_fails() {
             let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
             // change caller
             let account: AccountId = AccountId::from([0x2; 32]);
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(account);
-            token_price_storage.set_price("abc".to_string(), 100);
+            assert_eq!(
+                token_price_storage.set_price("abc".to_string(), 100),
+                Err(OracleError::NotUpdater)
+            );
+            assert_eq!(
+                token_price_storage.set_prices(vec![("abc".to_string(), 100)]),
+                Err(OracleError::NotUpdater)
+            );
+            assert_eq!(token_price_storage.get_latest_price("abc".to_string()), None);
         }
 
         #[ink::test]
-        #[should_panic]
-        fn set_updater_panic() {
+        fn owner_messages_fail() {
             let mut token_price_storage: TokenPriceStorage = TokenPriceStorage::new();
             // change caller
             let account: AccountId = AccountId::from([0x2; 32]);
             ink::env::test::set_caller::<ink::env::DefaultEnvironment>(account);
-            token_price_storage.set_updater(AccountId::from([0x01; 32]));
+            assert_eq!(
+                token_price_storage.set_updater(AccountId::from([0x01; 32])),
+                Err(OracleError::NotOwner)
+            );
+            assert_eq!(
+                token_price_storage.add_updater(account),
+                Err(OracleError::NotOwner)
+            );
+            assert_eq!(
+                token_price_storage.remove_updater(AccountId::from([0x01; 32])),
+                Err(OracleError::NotOwner)
+            );
+            assert_eq!(
+                token_price_storage.set_aggregation(1, 0),
+                Err(OracleError::NotOwner)
+            );
+            assert_eq!(
+                token_price_storage.transfer_ownership(account),
+                Err(OracleError::NotOwner)
+            );
+            assert_eq!(
+                token_price_storage.set_code([0x01; 32]),
+                Err(OracleError::NotOwner)
+            );
         }
     }
     #[cfg(all(test, feature = "e2e-tests"))]
@@ -0,0 +1,220 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+pub use self::psp22_token::Psp22TokenRef;
+
+// A minimal PSP22 token used as a local stand-in for stablecoins
+// when testing DAO token payouts and contributions.
+#[ink::contract]
+pub mod psp22_token {
+    use ink::prelude::string::String;
+    use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+
+    use psp22::{PSP22Error, PSP22Metadata, PSP22};
+
+    // Decimals used by `new`, matching the native token.
+    pub const DEFAULT_DECIMALS: u8 = 12;
+
+    #[ink(storage)]
+    pub struct Psp22Token {
+        // Total token supply
+        total_supply: Balance,
+        // Token balance of each account
+        balances: Mapping<AccountId, Balance>,
+        // Key: (owner, spender), value: allowance
+        allowances: Mapping<(AccountId, AccountId), Balance>,
+        // Number of decimals reported through PSP22Metadata
+        decimals: u8,
+    }
+
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        value: Balance,
+    }
+
+    impl Psp22Token {
+        // Mint the whole supply to the deployer.
+        #[ink(constructor)]
+        pub fn new(total_supply: Balance) -> Self {
+            Self::new_with_decimals(total_supply, DEFAULT_DECIMALS)
+        }
+
+        // Mint the whole supply to the deployer with custom decimals.
+        #[ink(constructor)]
+        pub fn new_with_decimals(total_supply: Balance, decimals: u8) -> Self {
+            let caller = Self::env().caller();
+            let mut balances = Mapping::default();
+            balances.insert(caller, &total_supply);
+            Self::env().emit_event(Transfer {
+                from: None,
+                to: Some(caller),
+                value: total_supply,
+            });
+            Self {
+                total_supply,
+                balances,
+                allowances: Mapping::default(),
+                decimals,
+            }
+        }
+
+        fn _transfer_from_to(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<(), PSP22Error> {
+            let from_balance = self.balances.get(from).unwrap_or_default();
+            if from_balance < value {
+                return Err(PSP22Error::InsufficientBalance);
+            }
+            let to_balance = self.balances.get(to).unwrap_or_default();
+            self.balances.insert(from, &(from_balance - value));
+            self.balances.insert(to, &(to_balance + value));
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: Some(to),
+                value,
+            });
+            Ok(())
+        }
+    }
+
+    impl PSP22 for Psp22Token {
+        #[ink(message)]
+        fn total_supply(&self) -> Balance {
+            self.total_supply
+        }
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balances.get(owner).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.allowances.get((owner, spender)).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn transfer(
+            &mut self,
+            to: AccountId,
+            value: Balance,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            let from = self.env().caller();
+            self._transfer_from_to(from, to, value)
+        }
+
+        #[ink(message)]
+        fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            let caller = self.env().caller();
+            let allowance = self.allowances.get((from, caller)).unwrap_or_default();
+            if allowance < value {
+                return Err(PSP22Error::InsufficientAllowance);
+            }
+            self._transfer_from_to(from, to, value)?;
+            self.allowances.insert((from, caller), &(allowance - value));
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn approve(&mut self, spender: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let owner = self.env().caller();
+            self.allowances.insert((owner, spender), &value);
+            self.env().emit_event(Approval {
+                owner,
+                spender,
+                value,
+            });
+            Ok(())
+        }
+    }
+
+    impl PSP22Metadata for Psp22Token {
+        #[ink(message)]
+        fn token_name(&self) -> Option<String> {
+            None
+        }
+
+        #[ink(message)]
+        fn token_symbol(&self) -> Option<String> {
+            None
+        }
+
+        #[ink(message)]
+        fn token_decimals(&self) -> u8 {
+            self.decimals
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink::env::test::{default_accounts, set_caller};
+
+        #[ink::test]
+        fn new_works() {
+            let accounts = default_accounts::<Environment>();
+            let token = Psp22Token::new(1000);
+            assert_eq!(token.total_supply(), 1000);
+            assert_eq!(token.balance_of(accounts.alice), 1000);
+            assert_eq!(token.token_decimals(), DEFAULT_DECIMALS);
+
+            let token = Psp22Token::new_with_decimals(1000, 6);
+            assert_eq!(token.token_decimals(), 6);
+        }
+
+        #[ink::test]
+        fn transfer_works() {
+            let accounts = default_accounts::<Environment>();
+            let mut token = Psp22Token::new(1000);
+            assert_eq!(token.transfer(accounts.bob, 100, Vec::new()), Ok(()));
+            assert_eq!(token.balance_of(accounts.alice), 900);
+            assert_eq!(token.balance_of(accounts.bob), 100);
+            assert_eq!(
+                token.transfer(accounts.bob, 1000, Vec::new()),
+                Err(PSP22Error::InsufficientBalance)
+            );
+        }
+
+        #[ink::test]
+        fn transfer_from_works() {
+            let accounts = default_accounts::<Environment>();
+            let mut token = Psp22Token::new(1000);
+            assert_eq!(token.approve(accounts.bob, 100), Ok(()));
+
+            set_caller::<Environment>(accounts.bob);
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.charlie, 200, Vec::new()),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+            assert_eq!(
+                token.transfer_from(accounts.alice, accounts.charlie, 100, Vec::new()),
+                Ok(())
+            );
+            assert_eq!(token.balance_of(accounts.charlie), 100);
+            assert_eq!(token.allowance(accounts.alice, accounts.bob), 0);
+        }
+    }
+}
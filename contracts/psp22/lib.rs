@@ -0,0 +1,61 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::primitives::AccountId;
+
+// Errors defined by the PSP22 standard.
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum PSP22Error {
+    Custom(String),
+    InsufficientBalance,
+    InsufficientAllowance,
+    ZeroRecipientAddress,
+    ZeroSenderAddress,
+    SafeTransferCheckFailed(String),
+}
+
+// The subset of the PSP22 fungible token standard used by DAOs.
+// Message selectors follow the standard ("PSP22::transfer", ...),
+// so any PSP22 compliant token can be called through this trait.
+#[ink::trait_definition]
+pub trait PSP22 {
+    #[ink(message)]
+    fn total_supply(&self) -> u128;
+
+    #[ink(message)]
+    fn balance_of(&self, owner: AccountId) -> u128;
+
+    #[ink(message)]
+    fn allowance(&self, owner: AccountId, spender: AccountId) -> u128;
+
+    #[ink(message)]
+    fn transfer(&mut self, to: AccountId, value: u128, data: Vec<u8>) -> Result<(), PSP22Error>;
+
+    #[ink(message)]
+    fn transfer_from(
+        &mut self,
+        from: AccountId,
+        to: AccountId,
+        value: u128,
+        data: Vec<u8>,
+    ) -> Result<(), PSP22Error>;
+
+    #[ink(message)]
+    fn approve(&mut self, spender: AccountId, value: u128) -> Result<(), PSP22Error>;
+}
+
+// Optional PSP22 metadata extension. DAOs read `token_decimals`
+// to scale fiat payouts into token units.
+#[ink::trait_definition]
+pub trait PSP22Metadata {
+    #[ink(message)]
+    fn token_name(&self) -> Option<String>;
+
+    #[ink(message)]
+    fn token_symbol(&self) -> Option<String>;
+
+    #[ink(message)]
+    fn token_decimals(&self) -> u8;
+}